@@ -1,34 +1,27 @@
 use crate::{
-    highlight::Highlighter,
-    project::Project,
-    widget::{self, DockArea, Widget, WidgetId, WidgetKind, escape, snap_pos_with_grid},
+    assets::{self, Asset, AssetId, AssetKind},
+    codegen::{
+        self, CodeGenFormat, CodeGenLayout, CodeGenerator, ComponentOptions, GenContext,
+        SingleFile, SourceMap, Visibility,
+    },
+    diff::{ChangeTag, Diff},
+    highlight::{self, Highlighter},
+    import,
+    lint::{self, Severity},
+    prefs::Prefs,
+    project::{LoadError, Project},
+    recovery::{self, Recovery},
+    search,
+    widget::{
+        Anchor, DockArea, HAnchor, Slot, VAnchor, Widget, WidgetId, WidgetKind, snap_pos_with_grid,
+    },
 };
 use chrono::{Datelike, NaiveDate};
 use egui::{Color32, CornerRadius, Id, Pos2, Rect, Sense, Stroke, UiBuilder, pos2, vec2};
 use egui_extras::DatePickerButton;
-use std::path::PathBuf;
-
-/// Code generation output format
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
-pub enum CodeGenFormat {
-    /// Single file with all code
-    #[default]
-    SingleFile,
-    /// Separate files: main.rs, state.rs, ui.rs
-    SeparateFiles,
-    /// Just the UI function (for embedding)
-    UiOnly,
-}
-
-impl CodeGenFormat {
-    pub const fn display_name(&self) -> &'static str {
-        match self {
-            CodeGenFormat::SingleFile => "Single File",
-            CodeGenFormat::SeparateFiles => "Separate Files",
-            CodeGenFormat::UiOnly => "UI Function Only",
-        }
-    }
-}
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
 
 pub(crate) struct RadBuilderApp {
     palette_open: bool,
@@ -61,16 +54,86 @@ pub(crate) struct RadBuilderApp {
     highlighter: Highlighter,
     /// Whether to show syntax highlighting (can be toggled for performance)
     syntax_highlighting: bool,
+    /// Settings kept between sessions
+    prefs: Prefs,
     /// Auto-generate code on widget changes
     auto_generate: bool,
+    /// `Project::revision` when edits were last checked for
+    seen_revision: u64,
+    /// When the project last changed, in seconds of `egui::InputState::time`
+    edited_at: f64,
+    /// `Project::revision` when the project was last saved, opened or
+    /// created; it has unsaved changes while the revision differs
+    saved_revision: u64,
+    /// Unsaved even at `saved_revision`, because the project differs from
+    /// its file without having been edited (a lenient load left items out)
+    force_unsaved: bool,
+    /// `Project::revision` last written to the recovery file
+    autosaved_revision: u64,
+    /// When the recovery file was last written, in seconds of `egui::InputState::time`
+    autosaved_at: f64,
+    /// Autosaved work found on startup, until it's restored or discarded
+    recovery: Option<Recovery>,
+    /// A project that failed to read, shown until dismissed
+    load_diagnostics: Option<LoadDiagnostics>,
+    /// New or Open waiting on the user to confirm discarding unsaved changes
+    pending_discard: Option<Discard>,
+    /// Window title last set, so it's only sent when it changes
+    title: String,
+    /// Project revision and settings `generated` was produced from
+    generated_for: Option<CodegenKey>,
     /// Code generation output format
     codegen_format: CodeGenFormat,
+    /// Absolute placement or inferred flow layout
+    codegen_layout: CodeGenLayout,
     /// Add comments to generated code
     codegen_comments: bool,
+    /// Emit a wasm32 entry point and index.html for trunk
+    codegen_web: bool,
+    /// Struct name and visibility for the component format
+    codegen_component: ComponentOptions,
+    /// Where each widget's code is in `generated`; empty once it's edited
+    source_map: SourceMap,
+    /// Selected widget the code view last scrolled to
+    code_scrolled_to: Option<WidgetId>,
+    /// Output before the last regeneration that changed it
+    previous_generated: String,
+    /// Show the Code tab as a diff against `diff_base`
+    show_diff: bool,
+    /// What the diff view compares the generated code against
+    diff_base: DiffBase,
+    /// Diff last shown, with a hash of the two texts it compares
+    diff_cache: Option<(u64, Diff)>,
+    /// Ctrl+F find bar of the Code tab
+    code_find: CodeFind,
+    /// Text searched for in the Find tab
+    find_query: String,
+    /// What Replace All in the Find tab puts in its place
+    find_replacement: String,
+    find_match_case: bool,
+    /// Focus the Find tab's search box next frame
+    find_focus: bool,
+    /// What the last Rust import couldn't map back to widgets
+    import_warnings: Vec<String>,
     /// Preview mode: interact with widgets without selection handles
     preview_mode: bool,
-    /// Active tab in the right panel (0 = Inspector, 1 = Code Output)
+    /// Active tab in the right panel (0 = Inspector, 1 = Code Output, 2 = Assets, 3 = Find, 4 = Problems)
     right_panel_tab: usize,
+    /// Asset selected in the Assets panel
+    selected_asset: Option<AssetId>,
+    /// Name typed for the selected asset that can't be applied yet
+    /// (blank or taken), kept so the field doesn't reset while typing
+    asset_name_edit: Option<(AssetId, String)>,
+    /// Embedded asset URIs already handed to egui's bytes loader
+    registered_bytes: HashSet<String>,
+    /// Fonts currently installed in the builder context (id, name, path, embedded)
+    font_signature: Vec<(AssetId, String, String, bool)>,
+    /// Widgets moved or resized during the current canvas drag
+    moved_widgets: HashSet<WidgetId>,
+    /// Container positions last frame, so tab content can follow its tab bar
+    container_pos: HashMap<WidgetId, Pos2>,
+    /// Where the canvas resize in progress started from
+    resize_base: Option<ResizeBase>,
 }
 
 impl Default for RadBuilderApp {
@@ -95,16 +158,75 @@ impl Default for RadBuilderApp {
             drag_select_start: None,
             highlighter: Highlighter::new(),
             syntax_highlighting: true,
+            prefs: Prefs::default(),
             auto_generate: false,
+            seen_revision: 0,
+            saved_revision: 0,
+            force_unsaved: false,
+            autosaved_revision: 0,
+            autosaved_at: 0.0,
+            recovery: None,
+            load_diagnostics: None,
+            pending_discard: None,
+            title: String::new(),
+            edited_at: 0.0,
+            generated_for: None,
             codegen_format: CodeGenFormat::default(),
+            codegen_layout: CodeGenLayout::default(),
             codegen_comments: true,
+            codegen_web: false,
+            codegen_component: ComponentOptions::default(),
+            source_map: SourceMap::default(),
+            code_scrolled_to: None,
+            previous_generated: String::new(),
+            show_diff: false,
+            diff_base: DiffBase::Previous,
+            diff_cache: None,
+            code_find: CodeFind::default(),
+            find_query: String::new(),
+            find_replacement: String::new(),
+            find_match_case: false,
+            find_focus: false,
+            import_warnings: Vec::new(),
             preview_mode: false,
             right_panel_tab: 0,
+            selected_asset: None,
+            asset_name_edit: None,
+            registered_bytes: HashSet::new(),
+            font_signature: Vec::new(),
+            moved_widgets: HashSet::new(),
+            container_pos: HashMap::new(),
+            resize_base: None,
         }
     }
 }
 
 impl RadBuilderApp {
+    /// The builder with the settings kept from the last session
+    pub(crate) fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let prefs = Prefs::load(cc.storage);
+        let mut app = Self {
+            palette_open: prefs.palette_open,
+            grid_size: prefs.grid_size,
+            show_grid: prefs.show_grid,
+            syntax_highlighting: prefs.syntax_highlighting,
+            auto_generate: prefs.auto_generate,
+            codegen_format: prefs.codegen_format,
+            codegen_comments: prefs.codegen_comments,
+            prefs,
+            recovery: recovery::load(),
+            ..Self::default()
+        };
+        // opening a file would clear the recovery before the user chose to restore it
+        if app.prefs.reopen_last
+            && app.recovery.is_none()
+            && let Some(path) = app.prefs.recent_files.first().cloned()
+        {
+            app.load_project(path);
+        }
+        app
+    }
+
     fn area_at(&self, pos: Pos2) -> DockArea {
         if let Some(r) = self.live_top
             && r.contains(pos)
@@ -169,8 +291,12 @@ impl RadBuilderApp {
             z: id.as_z(),
             area,
             props,
+            anchor: Anchor::default(),
+            slot: None,
         };
         self.project.widgets.push(w);
+        self.reslot(id);
+        self.project.touch();
         self.selected = vec![id];
     }
 
@@ -187,7 +313,6 @@ impl RadBuilderApp {
     }
 
     /// Select a single widget (clears other selections)
-    #[allow(dead_code)]
     fn select_single(&mut self, id: WidgetId) {
         self.selected = vec![id];
     }
@@ -218,10 +343,20 @@ impl RadBuilderApp {
 
     /// Save project to file
     fn save_project(&mut self, path: PathBuf) {
-        match serde_json::to_string_pretty(&self.project) {
+        // asset paths are relative to the project file, which may be moving
+        let mut assets = self.project.assets.clone();
+        assets::rebase_assets(&mut assets, self.project_dir().as_deref(), path.parent());
+        let project = Project {
+            assets,
+            ..self.project.clone()
+        };
+        match serde_json::to_string_pretty(&project) {
             Ok(json) => match std::fs::write(&path, &json) {
                 Ok(_) => {
+                    self.project.assets = project.assets;
                     self.current_file = Some(path.clone());
+                    self.mark_saved();
+                    self.prefs.add_recent(&path);
                     self.set_status(format!("Saved to {}", path.display()));
                 }
                 Err(e) => self.set_status(format!("Save failed: {}", e)),
@@ -230,26 +365,222 @@ impl RadBuilderApp {
         }
     }
 
+    /// Save to the current file, or ask for one. Returns whether it saved.
+    fn save(&mut self) -> bool {
+        let path = self.current_file.clone().or_else(|| {
+            rfd::FileDialog::new()
+                .add_filter("RAD Project", &["json", "rad"])
+                .set_file_name("project.json")
+                .save_file()
+        });
+        if let Some(path) = path {
+            self.save_project(path);
+        }
+        !self.has_unsaved_changes()
+    }
+
+    /// The project now matches what's on disk (or is a fresh one), so
+    /// there's nothing left to recover
+    fn mark_saved(&mut self) {
+        self.saved_revision = self.project.revision;
+        self.force_unsaved = false;
+        self.autosaved_revision = self.project.revision;
+        recovery::clear();
+    }
+
+    fn has_unsaved_changes(&self) -> bool {
+        self.force_unsaved || self.project.revision != self.saved_revision
+    }
+
+    fn new_project(&mut self) {
+        self.replace_project(Project::default());
+        self.selected.clear();
+        self.selected_asset = None;
+        self.import_warnings.clear();
+        self.current_file = None;
+        self.mark_saved();
+        self.set_status("New project created".into());
+    }
+
+    fn open_project(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("RAD Project", &["json", "rad"])
+            .pick_file()
+        {
+            self.load_project(path);
+        }
+    }
+
+    /// Swap in another project, continuing the revision count so nothing
+    /// mistakes it for the design it replaces
+    fn replace_project(&mut self, project: Project) {
+        let revision = self.project.revision;
+        self.project = project;
+        self.project.revision = revision;
+        self.project.touch();
+    }
+
+    /// Replace the project with one rebuilt from generated Rust code
+    fn import_rust(&mut self, path: PathBuf) {
+        let imported = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|src| import::import_code(&src));
+        match imported {
+            Ok(imported) => {
+                let max_id = imported.project.widgets.iter().map(|w| w.id).max();
+                self.next_id = max_id.map_or(1, |id| id.as_u64() + 1);
+                self.set_status(format!(
+                    "Imported {} widget(s) from {} ({} warning(s))",
+                    imported.project.widgets.len(),
+                    path.display(),
+                    imported.warnings.len()
+                ));
+                self.replace_project(imported.project);
+                self.import_warnings = imported.warnings;
+                self.selected.clear();
+                self.selected_asset = None;
+                self.current_file = None;
+            }
+            Err(e) => self.set_status(format!("Import failed: {}", e)),
+        }
+    }
+
     /// Load project from file
     fn load_project(&mut self, path: PathBuf) {
         match std::fs::read_to_string(&path) {
-            Ok(json) => {
-                match serde_json::from_str::<Project>(&json) {
-                    Ok(project) => {
-                        // Find max widget id to continue numbering
-                        let max_id = project.widgets.iter().map(|w| w.id).max();
-                        if let Some(id) = max_id {
-                            self.next_id = id.as_z() as u64 + 1;
-                        }
-                        self.project = project;
+            Ok(json) => match Project::from_json(&json) {
+                Ok(project) => self.open_loaded(project, path),
+                Err(e) => {
+                    self.set_status(format!("Failed to open {}: {e}", path.display()));
+                    self.load_diagnostics = Some(LoadDiagnostics {
+                        file: Some(path),
+                        json,
+                        error: Some(e),
+                        problems: Vec::new(),
+                    });
+                }
+            },
+            Err(e) => {
+                self.prefs.recent_files.retain(|p| *p != path);
+                self.set_status(format!("Load failed: {}", e));
+            }
+        }
+    }
+
+    /// Make `project`, read from `path`, the one being edited
+    fn open_loaded(&mut self, project: Project, path: PathBuf) {
+        // Find max widget id to continue numbering
+        let max_id = project.widgets.iter().map(|w| w.id).max();
+        if let Some(id) = max_id {
+            self.next_id = id.as_z() as u64 + 1;
+        }
+        self.replace_project(project);
+        self.selected.clear();
+        self.selected_asset = None;
+        self.import_warnings.clear();
+        self.current_file = Some(path.clone());
+        self.mark_saved();
+        self.prefs.add_recent(&path);
+        let missing = self.missing_assets();
+        if missing.is_empty() {
+            self.set_status(format!("Loaded {}", path.display()));
+        } else {
+            self.set_status(format!(
+                "Loaded {} ({} missing asset(s): {})",
+                path.display(),
+                missing.len(),
+                missing.join(", ")
+            ));
+        }
+    }
+
+    /// Why the last project load failed, with the offending lines, and
+    /// a lenient retry that lists what it leaves out
+    fn diagnostics_window(&mut self, ctx: &egui::Context) {
+        let Some(diagnostics) = &self.load_diagnostics else {
+            return;
+        };
+        let source = diagnostics
+            .file
+            .as_ref()
+            .map_or("the JSON in the code editor".into(), |p| {
+                p.display().to_string()
+            });
+        let mut open = true;
+        let mut lenient = false;
+        egui::Window::new("Load Problems")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(460.0)
+            .show(ctx, |ui| match &diagnostics.error {
+                Some(error) => {
+                    ui.label(format!("Couldn't read {source}:"));
+                    ui.colored_label(ui.visuals().error_fg_color, &error.message);
+                    if let Some((line, column)) = error.location {
+                        ui.label(format!("at line {line}, column {column}"));
+                    }
+                    if !error.snippet.is_empty() {
+                        egui::ScrollArea::horizontal().show(ui, |ui| {
+                            ui.add(
+                                egui::Label::new(egui::RichText::new(&error.snippet).monospace())
+                                    .extend(),
+                            );
+                        });
+                    }
+                    ui.separator();
+                    lenient = ui
+                        .button("Load Leniently")
+                        .on_hover_text(
+                            "Skip widgets and fields that can't be read, and list them here",
+                        )
+                        .clicked();
+                }
+                None => {
+                    ui.label(format!(
+                        "Read {source} leniently, leaving out {} item(s):",
+                        diagnostics.problems.len()
+                    ));
+                    egui::ScrollArea::vertical()
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            for problem in &diagnostics.problems {
+                                ui.label(format!("⚠ {problem}"));
+                            }
+                        });
+                }
+            });
+        if !open {
+            self.load_diagnostics = None;
+        }
+        if !lenient {
+            return;
+        }
+        let Some(mut diagnostics) = self.load_diagnostics.take() else {
+            return;
+        };
+        match Project::from_json_lenient(&diagnostics.json) {
+            Ok(salvaged) => {
+                match diagnostics.file.clone() {
+                    Some(path) => {
+                        self.open_loaded(salvaged.project, path);
+                        // the file still has what was left out, until saved over
+                        self.force_unsaved = true;
+                    }
+                    None => {
+                        self.replace_project(salvaged.project);
                         self.selected.clear();
-                        self.current_file = Some(path.clone());
-                        self.set_status(format!("Loaded {}", path.display()));
                     }
-                    Err(e) => self.set_status(format!("Parse failed: {}", e)),
                 }
+                diagnostics.error = None;
+                diagnostics.problems = salvaged.problems;
+                if !diagnostics.problems.is_empty() {
+                    self.load_diagnostics = Some(diagnostics);
+                }
+            }
+            Err(e) => {
+                diagnostics.error = Some(e);
+                self.load_diagnostics = Some(diagnostics);
             }
-            Err(e) => self.set_status(format!("Load failed: {}", e)),
         }
     }
 
@@ -258,6 +589,165 @@ impl RadBuilderApp {
         self.status_message = Some((msg, std::time::Instant::now()));
     }
 
+    /// Directory of the current project file; asset paths are relative to it
+    fn project_dir(&self) -> Option<PathBuf> {
+        self.current_file
+            .as_ref()
+            .and_then(|p| p.parent())
+            .map(Path::to_path_buf)
+    }
+
+    /// Names of assets whose files can't be found
+    fn missing_assets(&self) -> Vec<String> {
+        let base = self.project_dir();
+        self.project
+            .assets
+            .iter()
+            .filter(|a| !a.exists(base.as_deref()))
+            .map(|a| a.name.clone())
+            .collect()
+    }
+
+    /// URI the builder uses to preview an asset
+    fn asset_uri(asset: &Asset, base: Option<&Path>) -> String {
+        if asset.data.is_some() {
+            format!("bytes://asset/{}/{}", asset.id, asset.file_name())
+        } else {
+            format!("file://{}", asset.resolve(base).display())
+        }
+    }
+
+    /// Pick files of the given kind and add them to the project
+    fn import_assets(&mut self, kind: AssetKind) {
+        let Some(paths) = rfd::FileDialog::new()
+            .add_filter(kind.display_name(), kind.extensions())
+            .pick_files()
+        else {
+            return;
+        };
+        let base = self.project_dir();
+        let mut imported = 0;
+        for path in paths {
+            if AssetKind::from_path(&path) != Some(kind) {
+                self.set_status(format!("Unsupported file: {}", path.display()));
+                continue;
+            }
+            if kind == AssetKind::Font {
+                let valid = std::fs::read(&path)
+                    .map(|bytes| ab_glyph::FontRef::try_from_slice(&bytes).is_ok())
+                    .unwrap_or(false);
+                if !valid {
+                    self.set_status(format!("Not a valid font: {}", path.display()));
+                    continue;
+                }
+            }
+            let id = self.project.next_asset_id();
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| format!("asset_{}", id));
+            let name = self.project.unique_asset_name(&stem);
+            self.project.assets.push(Asset {
+                id,
+                kind,
+                name,
+                path: assets::relative_path(&path, base.as_deref()),
+                data: None,
+            });
+            self.selected_asset = Some(id);
+            imported += 1;
+        }
+        if imported > 0 {
+            self.project.touch();
+            self.set_status(format!("Imported {} asset(s)", imported));
+        }
+    }
+
+    /// Remove an asset and clear any widget references to it
+    fn remove_asset(&mut self, id: AssetId) {
+        self.project.assets.retain(|a| a.id != id);
+        self.project.touch();
+        for w in &mut self.project.widgets {
+            if w.props.asset == Some(id) {
+                w.props.asset = None;
+            }
+        }
+        if self.selected_asset == Some(id) {
+            self.selected_asset = None;
+        }
+    }
+
+    /// Keep egui's loaders and font set in step with the project's assets
+    fn sync_assets(&mut self, ctx: &egui::Context) {
+        for a in &self.project.assets {
+            if let Some(data) = &a.data {
+                let uri = Self::asset_uri(a, None);
+                if self.registered_bytes.insert(uri.clone()) {
+                    ctx.include_bytes(uri, data.clone());
+                }
+            }
+        }
+
+        let signature: Vec<_> = self
+            .project
+            .fonts()
+            .map(|a| (a.id, a.name.clone(), a.path.clone(), a.data.is_some()))
+            .collect();
+        if signature != self.font_signature {
+            let base = self.project_dir();
+            let mut fonts = egui::FontDefinitions::default();
+            for a in self.project.fonts() {
+                // egui panics on malformed font data, so only install fonts that parse
+                let Ok(bytes) = a.load_bytes(base.as_deref()) else {
+                    continue;
+                };
+                if ab_glyph::FontRef::try_from_slice(&bytes).is_err() {
+                    continue;
+                }
+                assets::install_font(&mut fonts, a, egui::FontData::from_owned(bytes));
+            }
+            ctx.set_fonts(fonts);
+            self.font_signature = signature;
+        }
+    }
+
+    /// Write Cargo.toml, src/main.rs and assets into a folder, plus
+    /// index.html when the web entry point is on
+    fn export_project(&mut self, dir: PathBuf) {
+        let name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase().replace([' ', '.'], "-"))
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| "generated-ui".to_owned());
+        let result = (|| -> std::io::Result<usize> {
+            std::fs::create_dir_all(dir.join("src"))?;
+            std::fs::write(
+                dir.join("Cargo.toml"),
+                codegen::cargo_toml(&self.project, &name, self.codegen_web),
+            )?;
+            if self.codegen_web {
+                std::fs::write(dir.join("index.html"), codegen::index_html(&name))?;
+            }
+            std::fs::write(
+                dir.join("src/main.rs"),
+                SingleFile {
+                    cx: self.codegen_context(),
+                }
+                .generate(),
+            )?;
+            let base = self.project_dir();
+            assets::export_assets(&self.project.assets, base.as_deref(), &dir)
+        })();
+        match result {
+            Ok(n) => self.set_status(format!(
+                "Exported project to {} ({} asset(s))",
+                dir.display(),
+                n
+            )),
+            Err(e) => self.set_status(format!("Export failed: {}", e)),
+        }
+    }
+
     /// Get widgets in selection rect (for drag-box selection)
     #[allow(dead_code)]
     fn widgets_in_rect(&self, rect: Rect, area_origin: Pos2) -> Vec<WidgetId> {
@@ -278,6 +768,21 @@ impl RadBuilderApp {
         // Optional: stable visual order
         self.project.widgets.sort_by_key(|w| w.z);
 
+        // Resolve preview URIs for image widgets backed by project assets
+        let base = self.project_dir();
+        let image_uris: HashMap<WidgetId, String> = self
+            .project
+            .widgets
+            .iter()
+            .filter_map(|w| {
+                let asset = self.project.widget_image(w)?;
+                Some((w.id, Self::asset_uri(asset, base.as_deref())))
+            })
+            .collect();
+
+        // set when a preview widget (checkbox, slider, ...) changes its props
+        let mut edited = false;
+
         // Reset live rects each frame
         self.live_top = None;
         self.live_bottom = None;
@@ -293,7 +798,22 @@ impl RadBuilderApp {
         let mut center_idx = Vec::new();
         let mut free_idx = Vec::new();
 
+        // children of inactive tabs are hidden, like in the generated UI
+        let active_tabs: HashMap<WidgetId, usize> = self
+            .project
+            .widgets
+            .iter()
+            .filter(|w| w.kind == WidgetKind::TabBar)
+            .map(|w| (w.id, w.props.selected))
+            .collect();
         for (i, w) in self.project.widgets.iter().enumerate() {
+            if let Some(slot) = w.slot
+                && active_tabs
+                    .get(&slot.parent)
+                    .is_some_and(|&tab| tab != slot.row)
+            {
+                continue;
+            }
             match w.area {
                 Top => top_idx.push(i),
                 Bottom => bottom_idx.push(i),
@@ -316,7 +836,17 @@ impl RadBuilderApp {
                     }
                     for &i in &top_idx {
                         let w = &mut self.project.widgets[i];
-                        Self::draw_widget(ui, panel_rect, self.grid_size, &mut self.selected, w);
+                        if Self::draw_widget(
+                            ui,
+                            panel_rect,
+                            self.grid_size,
+                            &mut self.selected,
+                            &mut edited,
+                            &image_uris,
+                            w,
+                        ) {
+                            self.moved_widgets.insert(w.id);
+                        }
                     }
                 });
         }
@@ -333,7 +863,17 @@ impl RadBuilderApp {
                     }
                     for &i in &bottom_idx {
                         let w = &mut self.project.widgets[i];
-                        Self::draw_widget(ui, panel_rect, self.grid_size, &mut self.selected, w);
+                        if Self::draw_widget(
+                            ui,
+                            panel_rect,
+                            self.grid_size,
+                            &mut self.selected,
+                            &mut edited,
+                            &image_uris,
+                            w,
+                        ) {
+                            self.moved_widgets.insert(w.id);
+                        }
                     }
                 });
         }
@@ -350,7 +890,17 @@ impl RadBuilderApp {
                     }
                     for &i in &left_idx {
                         let w = &mut self.project.widgets[i];
-                        Self::draw_widget(ui, panel_rect, self.grid_size, &mut self.selected, w);
+                        if Self::draw_widget(
+                            ui,
+                            panel_rect,
+                            self.grid_size,
+                            &mut self.selected,
+                            &mut edited,
+                            &image_uris,
+                            w,
+                        ) {
+                            self.moved_widgets.insert(w.id);
+                        }
                     }
                 });
         }
@@ -367,7 +917,17 @@ impl RadBuilderApp {
                     }
                     for &i in &right_idx {
                         let w = &mut self.project.widgets[i];
-                        Self::draw_widget(ui, panel_rect, self.grid_size, &mut self.selected, w);
+                        if Self::draw_widget(
+                            ui,
+                            panel_rect,
+                            self.grid_size,
+                            &mut self.selected,
+                            &mut edited,
+                            &image_uris,
+                            w,
+                        ) {
+                            self.moved_widgets.insert(w.id);
+                        }
                     }
                 });
         }
@@ -388,11 +948,31 @@ impl RadBuilderApp {
             // Draw Center + Free widgets inside the center canvas
             for &i in &center_idx {
                 let w = &mut self.project.widgets[i];
-                Self::draw_widget(ui, painter_rect, self.grid_size, &mut self.selected, w);
+                if Self::draw_widget(
+                    ui,
+                    painter_rect,
+                    self.grid_size,
+                    &mut self.selected,
+                    &mut edited,
+                    &image_uris,
+                    w,
+                ) {
+                    self.moved_widgets.insert(w.id);
+                }
             }
             for &i in &free_idx {
                 let w = &mut self.project.widgets[i];
-                Self::draw_widget(ui, painter_rect, self.grid_size, &mut self.selected, w);
+                if Self::draw_widget(
+                    ui,
+                    painter_rect,
+                    self.grid_size,
+                    &mut self.selected,
+                    &mut edited,
+                    &image_uris,
+                    w,
+                ) {
+                    self.moved_widgets.insert(w.id);
+                }
             }
 
             // --- Drag ghost + drop ---
@@ -440,10 +1020,157 @@ impl RadBuilderApp {
                 }
             }
 
+            // Resize handle: dragging it reflows anchored widgets like a window resize would
+            let handle = Rect::from_center_size(canvas.max, vec2(12.0, 12.0));
+            let handle_resp = ui
+                .interact(handle, Id::new("canvas_resize"), egui::Sense::drag())
+                .on_hover_cursor(egui::CursorIcon::ResizeNwSe)
+                .on_hover_text("Drag to resize the canvas");
+            if handle_resp.dragged() {
+                let size = self.project.canvas_size + handle_resp.drag_delta();
+                self.resize_canvas(size.max(vec2(100.0, 100.0)));
+            }
+            ui.painter().rect_stroke(
+                canvas,
+                CornerRadius::ZERO,
+                Stroke::new(1.0, Color32::from_gray(70)),
+                egui::StrokeKind::Outside,
+            );
+            ui.painter().rect_filled(
+                handle.shrink(2.0),
+                2.0,
+                if handle_resp.hovered() || handle_resp.dragged() {
+                    Color32::LIGHT_BLUE
+                } else {
+                    Color32::from_gray(110)
+                },
+            );
+
             if resp.clicked() {
                 self.selected.clear();
             }
         });
+
+        if edited {
+            self.project.touch();
+        }
+        self.update_slots(ctx);
+    }
+
+    /// Keep container children in their cells. Widgets dropped onto a Grid
+    /// snap into the cell under their center; children follow their container.
+    fn update_slots(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.pointer.any_released()) {
+            let moved = std::mem::take(&mut self.moved_widgets);
+            for &id in &moved {
+                self.reslot(id);
+            }
+            if !moved.is_empty() {
+                self.project.touch();
+            }
+        }
+        let containers: HashMap<WidgetId, Widget> = self
+            .project
+            .widgets
+            .iter()
+            .filter(|w| w.kind.is_container())
+            .map(|w| (w.id, w.clone()))
+            .collect();
+        for w in &mut self.project.widgets {
+            let Some(slot) = &mut w.slot else {
+                continue;
+            };
+            match containers.get(&slot.parent) {
+                Some(parent) if parent.area == w.area => {
+                    slot.row = slot.row.min(parent.slot_rows() - 1);
+                    // a child being dragged stays under the pointer until it is dropped
+                    if self.moved_widgets.contains(&w.id) {
+                        continue;
+                    }
+                    if parent.kind == WidgetKind::TabBar {
+                        slot.col = 0;
+                        if let Some(old) = self.container_pos.get(&parent.id) {
+                            w.pos += parent.pos - *old;
+                        }
+                    } else {
+                        slot.col = slot.col.min(parent.props.columns.max(1) - 1);
+                        w.pos = parent.grid_cell(slot.row, slot.col).min;
+                    }
+                }
+                // container deleted or moved to another panel
+                _ => w.slot = None,
+            }
+        }
+        self.container_pos = containers.iter().map(|(id, c)| (*id, c.pos)).collect();
+    }
+
+    /// Put widget `id` into the container cell under its center, or detach it.
+    fn reslot(&mut self, id: WidgetId) {
+        let Some(w) = self.project.widgets.iter().find(|w| w.id == id) else {
+            return;
+        };
+        if w.kind.is_container() {
+            return;
+        }
+        let center = w.pos + w.size * 0.5;
+        let target = self
+            .project
+            .widgets
+            .iter()
+            .filter(|c| c.kind.is_container() && c.area == w.area)
+            .filter_map(|c| Some((c, c.slot_at(center)?)))
+            .max_by_key(|(c, _)| c.z)
+            .map(|(c, (row, col))| {
+                (
+                    Slot {
+                        parent: c.id,
+                        row,
+                        col,
+                    },
+                    c.z,
+                )
+            });
+        if let Some(w) = self.project.widgets.iter_mut().find(|w| w.id == id) {
+            w.slot = target.map(|(slot, _)| slot);
+            // children draw above their container
+            if let Some((_, z)) = target {
+                w.z = w.z.max(z + 1);
+            }
+        }
+    }
+
+    /// Change the design canvas size, moving/stretching anchored widgets the
+    /// same way the generated UI reacts to a window resize.
+    fn resize_canvas(&mut self, new: egui::Vec2) {
+        if self.project.canvas_size == new {
+            return;
+        }
+        let base = match self.resize_base.take() {
+            Some(base) if base.revision == self.project.revision => base,
+            _ => ResizeBase {
+                revision: 0,
+                canvas: self.project.canvas_size,
+                rects: self
+                    .project
+                    .widgets
+                    .iter()
+                    // grid and tab children follow their container instead
+                    .filter(|w| w.is_anchorable() && !w.anchor.is_fixed() && w.slot.is_none())
+                    .map(|w| (w.id, w.pos, w.size))
+                    .collect(),
+            },
+        };
+        for &(id, pos, size) in &base.rects {
+            if let Some(w) = self.project.widgets.iter_mut().find(|w| w.id == id) {
+                (w.pos, w.size) = w.anchor.apply(pos, size, base.canvas, new);
+            }
+        }
+        self.project.canvas_size = new;
+        self.project.touch();
+        self.resize_base = Some(ResizeBase {
+            revision: self.project.revision,
+            ..base
+        });
     }
 
     fn draw_grid(&self, ui: &mut egui::Ui, rect: Rect) {
@@ -472,9 +1199,12 @@ impl RadBuilderApp {
         canvas_rect: Rect,
         grid: f32,
         selected: &mut Vec<WidgetId>,
+        edited: &mut bool,
+        image_uris: &HashMap<WidgetId, String>,
         w: &mut Widget,
-    ) {
+    ) -> bool {
         let rect = Rect::from_min_size(canvas_rect.min + w.pos.to_vec2(), w.size);
+        let mut moved = false;
         ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
             match w.kind {
                 WidgetKind::MenuButton => {
@@ -483,16 +1213,15 @@ impl RadBuilderApp {
                     } else {
                         w.props.items.clone()
                     };
-                    let mut sel = w.props.selected.min(items.len() - 1);
                     ui.menu_button(&w.props.text, |ui| {
                         for (i, it) in items.iter().enumerate() {
                             if ui.button(it).clicked() {
-                                sel = i;
+                                w.props.selected = i;
+                                *edited = true;
                                 ui.close_kind(egui::UiKind::Menu);
                             }
                         }
                     });
-                    w.props.selected = sel;
                 }
                 WidgetKind::Label => {
                     ui.vertical_centered(|ui| {
@@ -510,21 +1239,29 @@ impl RadBuilderApp {
                 }
                 WidgetKind::Checkbox => {
                     let mut checked = w.props.checked;
-                    ui.add_sized(w.size, egui::Checkbox::new(&mut checked, &w.props.text));
-                    w.props.checked = checked;
+                    let resp =
+                        ui.add_sized(w.size, egui::Checkbox::new(&mut checked, &w.props.text));
+                    if resp.changed() {
+                        w.props.checked = checked;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::TextEdit => {
                     let mut buf = w.props.text.clone();
                     let resp = egui::TextEdit::singleline(&mut buf).hint_text("text");
-                    ui.add_sized(w.size, resp);
-                    w.props.text = buf;
+                    if ui.add_sized(w.size, resp).changed() {
+                        w.props.text = buf;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::Slider => {
                     let mut v = w.props.value;
                     let slider =
                         egui::Slider::new(&mut v, w.props.min..=w.props.max).text(&w.props.text);
-                    ui.add_sized(w.size, slider);
-                    w.props.value = v;
+                    if ui.add_sized(w.size, slider).changed() {
+                        w.props.value = v;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::ProgressBar => {
                     let bar =
@@ -532,15 +1269,19 @@ impl RadBuilderApp {
                     ui.add_sized(w.size, bar);
                 }
                 WidgetKind::RadioGroup => {
-                    let mut sel = w.props.selected.min(w.props.items.len().saturating_sub(1));
+                    let sel = w.props.selected.min(w.props.items.len().saturating_sub(1));
+                    let mut clicked = None;
                     ui.vertical(|ui| {
                         for (i, it) in w.props.items.iter().enumerate() {
                             if ui.add(egui::RadioButton::new(sel == i, it)).clicked() {
-                                sel = i;
+                                clicked = Some(i);
                             }
                         }
                     });
-                    w.props.selected = sel;
+                    if let Some(i) = clicked {
+                        w.props.selected = i;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::Link => {
                     let _ = ui.link(&w.props.text);
@@ -549,14 +1290,13 @@ impl RadBuilderApp {
                     ui.hyperlink_to(&w.props.text, &w.props.url);
                 }
                 WidgetKind::SelectableLabel => {
-                    let mut on = w.props.checked;
                     if ui
-                        .add(egui::Button::selectable(on, &w.props.text))
+                        .add(egui::Button::selectable(w.props.checked, &w.props.text))
                         .clicked()
                     {
-                        on = !on;
+                        w.props.checked = !w.props.checked;
+                        *edited = true;
                     }
-                    w.props.checked = on;
                 }
                 WidgetKind::ComboBox => {
                     let items = if w.props.items.is_empty() {
@@ -570,10 +1310,12 @@ impl RadBuilderApp {
                         .selected_text(items[sel].clone())
                         .show_ui(ui, |ui| {
                             for (i, it) in items.iter().enumerate() {
-                                ui.selectable_value(&mut sel, i, it.clone());
+                                if ui.selectable_value(&mut sel, i, it.clone()).changed() {
+                                    w.props.selected = sel;
+                                    *edited = true;
+                                }
                             }
                         });
-                    w.props.selected = sel;
                 }
                 WidgetKind::Separator => {
                     ui.separator();
@@ -592,13 +1334,16 @@ impl RadBuilderApp {
                         w.props.day.clamp(1, 28), // simple clamp
                     )
                     .unwrap_or_else(|| NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
-                    ui.horizontal(|ui| {
+                    let resp = ui.horizontal(|ui| {
                         ui.label(&w.props.text);
-                        ui.add(DatePickerButton::new(&mut date));
+                        ui.add(DatePickerButton::new(&mut date))
                     });
-                    w.props.year = date.year();
-                    w.props.month = date.month();
-                    w.props.day = date.day();
+                    if resp.inner.changed() {
+                        w.props.year = date.year();
+                        w.props.month = date.month();
+                        w.props.day = date.day();
+                        *edited = true;
+                    }
                 }
                 WidgetKind::AngleSelector => {
                     // Angle editor as slider in degrees
@@ -606,16 +1351,20 @@ impl RadBuilderApp {
                     let slider = egui::Slider::new(&mut v, w.props.min..=w.props.max)
                         .suffix("°")
                         .text(&w.props.text);
-                    ui.add_sized(w.size, slider);
-                    w.props.value = v;
+                    if ui.add_sized(w.size, slider).changed() {
+                        w.props.value = v;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::Password => {
                     let mut buf = w.props.text.clone();
                     let resp = egui::TextEdit::singleline(&mut buf)
                         .password(true)
                         .hint_text("password");
-                    ui.add_sized(w.size, resp);
-                    w.props.text = buf;
+                    if ui.add_sized(w.size, resp).changed() {
+                        w.props.text = buf;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::Tree => {
                     // Parse items (two leading spaces per level) into nodes:
@@ -696,16 +1445,21 @@ impl RadBuilderApp {
                     let resp = egui::TextEdit::multiline(&mut buf)
                         .desired_width(w.size.x)
                         .desired_rows(5);
-                    ui.add_sized(w.size, resp);
-                    w.props.text = buf;
+                    if ui.add_sized(w.size, resp).changed() {
+                        w.props.text = buf;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::DragValue => {
                     let mut v = w.props.value;
-                    ui.horizontal(|ui| {
+                    let resp = ui.horizontal(|ui| {
                         ui.label(&w.props.text);
-                        ui.add(egui::DragValue::new(&mut v).range(w.props.min..=w.props.max));
+                        ui.add(egui::DragValue::new(&mut v).range(w.props.min..=w.props.max))
                     });
-                    w.props.value = v;
+                    if resp.inner.changed() {
+                        w.props.value = v;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::Spinner => {
                     ui.add(egui::Spinner::new());
@@ -717,19 +1471,22 @@ impl RadBuilderApp {
                         w.props.color[2],
                         w.props.color[3],
                     );
-                    ui.horizontal(|ui| {
+                    let resp = ui.horizontal(|ui| {
                         ui.label(&w.props.text);
                         egui::color_picker::color_edit_button_srgba(
                             ui,
                             &mut color,
                             egui::color_picker::Alpha::OnlyBlend,
-                        );
+                        )
                     });
-                    w.props.color = [color.r(), color.g(), color.b(), color.a()];
+                    if resp.inner.changed() {
+                        w.props.color = [color.r(), color.g(), color.b(), color.a()];
+                        *edited = true;
+                    }
                 }
                 WidgetKind::Code => {
                     let mut buf = w.props.text.clone();
-                    egui::ScrollArea::vertical()
+                    let resp = egui::ScrollArea::vertical()
                         .auto_shrink([false, false])
                         .show(ui, |ui| {
                             ui.add(
@@ -737,9 +1494,12 @@ impl RadBuilderApp {
                                     .code_editor()
                                     .desired_width(w.size.x)
                                     .desired_rows(8),
-                            );
+                            )
                         });
-                    w.props.text = buf;
+                    if resp.inner.changed() {
+                        w.props.text = buf;
+                        *edited = true;
+                    }
                 }
                 WidgetKind::Heading => {
                     ui.heading(&w.props.text);
@@ -750,6 +1510,9 @@ impl RadBuilderApp {
                 WidgetKind::Monospace => {
                     ui.monospace(&w.props.text);
                 }
+                WidgetKind::Image if image_uris.contains_key(&w.id) => {
+                    ui.add(egui::Image::new(image_uris[&w.id].as_str()).fit_to_exact_size(w.size));
+                }
                 WidgetKind::Image => {
                     // Show placeholder with image info
                     let color = Color32::from_rgba_unmultiplied(80, 80, 80, 200);
@@ -816,14 +1579,23 @@ impl RadBuilderApp {
                         });
                 }
                 WidgetKind::TabBar => {
+                    // clicking a tab switches which children the canvas shows
                     ui.horizontal(|ui| {
                         for (i, item) in w.props.items.iter().enumerate() {
                             let selected = i == w.props.selected;
-                            if ui.selectable_label(selected, item).clicked() {
+                            if ui.selectable_label(selected, item).clicked() && !selected {
                                 w.props.selected = i;
+                                *edited = true;
                             }
                         }
                     });
+                    let content = w.tab_content().translate(canvas_rect.min.to_vec2());
+                    ui.painter().rect_stroke(
+                        content,
+                        0.0,
+                        Stroke::new(1.0, Color32::from_gray(70)),
+                        egui::StrokeKind::Inside,
+                    );
                 }
                 WidgetKind::Columns => {
                     let cols = w.props.columns.max(1);
@@ -839,6 +1611,24 @@ impl RadBuilderApp {
                             });
                         });
                 }
+                WidgetKind::Grid => {
+                    // Cells only; children are drawn as regular widgets on top
+                    let painter = ui.painter();
+                    let offset = canvas_rect.min.to_vec2();
+                    let line = Stroke::new(1.0, Color32::from_gray(70));
+                    for row in 0..w.props.rows.max(1) {
+                        if w.props.striped && row % 2 == 1 {
+                            let first = w.grid_cell(row, 0).translate(offset);
+                            let stripe =
+                                Rect::from_min_max(first.min, pos2(rect.max.x, first.max.y));
+                            painter.rect_filled(stripe, 0.0, ui.visuals().faint_bg_color);
+                        }
+                        for col in 0..w.props.columns.max(1) {
+                            let cell = w.grid_cell(row, col).translate(offset);
+                            painter.rect_stroke(cell, 0.0, line, egui::StrokeKind::Inside);
+                        }
+                    }
+                }
                 WidgetKind::Window => {
                     egui::Frame::window(ui.style()).show(ui, |ui| {
                         ui.set_min_size(w.size - vec2(16.0, 16.0));
@@ -922,6 +1712,7 @@ impl RadBuilderApp {
                 }
             }
             if drag_delta != egui::Vec2::ZERO {
+                moved = true;
                 w.pos += drag_delta;
                 w.pos = snap_pos_with_grid(w.pos, grid);
                 let maxx = (canvas_rect.width() - w.size.x).max(0.0);
@@ -941,6 +1732,7 @@ impl RadBuilderApp {
                 ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeNwSe);
             }
             if rresp.dragged() {
+                moved = true;
                 let delta = rresp.drag_delta();
                 w.size += delta;
                 w.size.x = w.size.x.max(20.0).min(canvas_rect.width());
@@ -949,6 +1741,7 @@ impl RadBuilderApp {
             ui.painter()
                 .rect_filled(handle, 2.0, Color32::from_rgb(100, 160, 255));
         }
+        moved
     }
 
     fn snap_pos(&self, p: Pos2) -> Pos2 {
@@ -1013,6 +1806,7 @@ impl RadBuilderApp {
                         self.palette_item(ui, "Group", WidgetKind::Group);
                         self.palette_item(ui, "Scroll Box", WidgetKind::ScrollBox);
                         self.palette_item(ui, "Columns", WidgetKind::Columns);
+                        self.palette_item(ui, "Grid", WidgetKind::Grid);
                         self.palette_item(ui, "Tab Bar", WidgetKind::TabBar);
                         self.palette_item(ui, "Window", WidgetKind::Window);
                         self.palette_item(ui, "Collapsing Header", WidgetKind::CollapsingHeader);
@@ -1049,8 +1843,25 @@ impl RadBuilderApp {
         }
     }
 
+    /// The inspector edits the selected widget in place; comparing it with a
+    /// copy taken beforehand tells whether this frame changed the project.
     fn inspector_ui(&mut self, ui: &mut egui::Ui) {
+        let before = self.selected_mut().cloned();
+        self.inspector_fields(ui);
+        if self.selected_mut().map(|w| &*w) != before.as_ref() {
+            self.project.touch();
+        }
+    }
+
+    fn inspector_fields(&mut self, ui: &mut egui::Ui) {
         let grid = self.grid_size; // read before mutably borrowing self
+        let image_assets: Vec<(AssetId, String)> = self
+            .project
+            .assets
+            .iter()
+            .filter(|a| a.kind == AssetKind::Image)
+            .map(|a| (a.id, a.name.clone()))
+            .collect();
         ui.heading("Inspector");
         ui.separator();
         if let Some(w) = self.selected_mut() {
@@ -1088,6 +1899,7 @@ impl RadBuilderApp {
                 | WidgetKind::Tree
                 | WidgetKind::Separator
                 | WidgetKind::Spinner
+                | WidgetKind::Grid
                 | WidgetKind::TabBar => {}
                 WidgetKind::MenuButton => {
                     ui.label("Text");
@@ -1102,10 +1914,26 @@ impl RadBuilderApp {
                     );
                 }
                 WidgetKind::Image => {
-                    ui.label("Filename");
-                    ui.text_edit_singleline(&mut w.props.text);
-                    ui.label("URI");
-                    ui.text_edit_singleline(&mut w.props.url);
+                    ui.label("Asset");
+                    let current = w
+                        .props
+                        .asset
+                        .and_then(|id| image_assets.iter().find(|(a, _)| *a == id))
+                        .map_or("(none: use URI)", |(_, name)| name.as_str());
+                    egui::ComboBox::from_id_salt(("image_asset", w.id))
+                        .selected_text(current)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut w.props.asset, None, "(none: use URI)");
+                            for (id, name) in &image_assets {
+                                ui.selectable_value(&mut w.props.asset, Some(*id), name);
+                            }
+                        });
+                    if w.props.asset.is_none() {
+                        ui.label("Filename");
+                        ui.text_edit_singleline(&mut w.props.text);
+                        ui.label("URI");
+                        ui.text_edit_singleline(&mut w.props.url);
+                    }
                 }
             }
             match w.kind {
@@ -1226,6 +2054,27 @@ impl RadBuilderApp {
                         ui.add(egui::DragValue::new(&mut w.props.columns).range(1..=10));
                     });
                 }
+                WidgetKind::Grid => {
+                    ui.horizontal(|ui| {
+                        ui.label("Rows");
+                        ui.add(egui::DragValue::new(&mut w.props.rows).range(1..=50));
+                        ui.label("Columns");
+                        ui.add(egui::DragValue::new(&mut w.props.columns).range(1..=20));
+                    });
+                    ui.checkbox(&mut w.props.striped, "striped");
+                    ui.horizontal(|ui| {
+                        ui.label("Spacing");
+                        ui.add(egui::DragValue::new(&mut w.props.spacing[0]).range(0.0..=64.0));
+                        ui.add(egui::DragValue::new(&mut w.props.spacing[1]).range(0.0..=64.0));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Min column width");
+                        ui.add(
+                            egui::DragValue::new(&mut w.props.min_col_width).range(0.0..=1000.0),
+                        );
+                    });
+                    ui.weak("Drop widgets onto a cell to place them in the grid");
+                }
                 _ => {}
             }
             ui.separator();
@@ -1250,8 +2099,23 @@ impl RadBuilderApp {
                     w.area = area;
                     // reset pos within new area (keeps roughly same coords snapped)
                     w.pos = snap_pos_with_grid(w.pos, grid);
+                    if !w.is_anchorable() {
+                        w.anchor = Anchor::default();
+                    }
                 }
             });
+            if let Some(slot) = w.slot {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "In #{} at row {}, column {}",
+                        slot.parent, slot.row, slot.col
+                    ));
+                    if ui.small_button("Detach").clicked() {
+                        w.slot = None;
+                        w.pos += vec2(20.0, 20.0);
+                    }
+                });
+            }
             ui.label("Position / Size");
             ui.horizontal(|ui| {
                 ui.label("x");
@@ -1265,11 +2129,45 @@ impl RadBuilderApp {
                 ui.label("h");
                 ui.add(egui::DragValue::new(&mut w.size.y).range(12.0..=2000.0));
             });
+            ui.label("Anchor");
+            let anchorable = w.is_anchorable();
+            ui.add_enabled_ui(anchorable, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("h");
+                    egui::ComboBox::from_id_salt(("anchor_h", w.id))
+                        .selected_text(format!("{:?}", w.anchor.h))
+                        .show_ui(ui, |ui| {
+                            for a in HAnchor::ALL {
+                                ui.selectable_value(&mut w.anchor.h, a, format!("{:?}", a));
+                            }
+                        });
+                    ui.label("v");
+                    egui::ComboBox::from_id_salt(("anchor_v", w.id))
+                        .selected_text(format!("{:?}", w.anchor.v))
+                        .show_ui(ui, |ui| {
+                            for a in VAnchor::ALL {
+                                ui.selectable_value(&mut w.anchor.v, a, format!("{:?}", a));
+                            }
+                        });
+                });
+            });
+            if !anchorable {
+                ui.weak("Anchors apply to Center/Free widgets only");
+            }
 
             ui.separator();
             ui.label("Tooltip (optional)");
             ui.text_edit_singleline(&mut w.props.tooltip);
 
+            if w.kind.is_accessible() {
+                ui.separator();
+                ui.label("Accessible name (optional)")
+                    .on_hover_text("What screen readers announce instead of the visible text");
+                ui.text_edit_singleline(&mut w.props.access_label);
+                ui.label("Accessible description (optional)");
+                ui.text_edit_singleline(&mut w.props.access_description);
+            }
+
             ui.add_space(6.0);
             if ui.button("Delete").clicked() {
                 let id = w.id; // capture
@@ -1281,6 +2179,153 @@ impl RadBuilderApp {
         }
     }
 
+    fn assets_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Assets");
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui
+                .button("Import Image...")
+                .on_hover_text("Add image files to the project")
+                .clicked()
+            {
+                self.import_assets(AssetKind::Image);
+            }
+            if ui
+                .button("Import Font...")
+                .on_hover_text("Add .ttf / .otf fonts to the project")
+                .clicked()
+            {
+                self.import_assets(AssetKind::Font);
+            }
+        });
+        if self.current_file.is_none() {
+            ui.small("Save the project first to store asset paths relative to it.");
+        }
+        ui.add_space(4.0);
+
+        let base = self.project_dir();
+        if self.project.assets.is_empty() {
+            ui.weak("No assets");
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_salt("asset_list_scroll")
+            .max_height(160.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for a in &self.project.assets {
+                    ui.horizontal(|ui| {
+                        let label = format!("{} {}", a.kind.icon(), a.name);
+                        if ui
+                            .selectable_label(self.selected_asset == Some(a.id), label)
+                            .clicked()
+                        {
+                            self.selected_asset = Some(a.id);
+                        }
+                        if !a.exists(base.as_deref()) {
+                            ui.colored_label(Color32::from_rgb(230, 160, 60), "⚠ missing")
+                                .on_hover_text(a.resolve(base.as_deref()).display().to_string());
+                        }
+                    });
+                }
+            });
+        ui.separator();
+
+        let Some(id) = self.selected_asset else {
+            ui.weak("No asset selected");
+            return;
+        };
+        let Some(mut name) = (match self.asset_name_edit.take() {
+            Some((edit, name)) if edit == id => Some(name),
+            _ => self.project.asset(id).map(|a| a.name.clone()),
+        }) else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut name);
+        });
+        let problem = self.project.asset_name_problem(id, &name);
+        if let Some(problem) = problem {
+            ui.colored_label(Color32::from_rgb(230, 160, 60), problem);
+            self.asset_name_edit = Some((id, name.clone()));
+        }
+        let mut remove = false;
+        let mut changed = false;
+        let mut status = None;
+        if let Some(a) = self.project.assets.iter_mut().find(|a| a.id == id) {
+            if problem.is_none() && a.name != name {
+                a.name = name;
+                changed = true;
+            }
+            ui.label(format!("Kind: {}", a.kind.display_name()));
+            ui.label("Path");
+            ui.monospace(&a.path);
+            let mut embedded = a.data.is_some();
+            if ui
+                .checkbox(&mut embedded, "Embed in project file")
+                .on_hover_text("Store the file contents inside the project JSON")
+                .changed()
+            {
+                if embedded {
+                    match a.load_bytes(base.as_deref()) {
+                        Ok(bytes) => {
+                            a.data = Some(bytes);
+                            changed = true;
+                        }
+                        Err(e) => status = Some(format!("Embed failed: {}", e)),
+                    }
+                } else if a.resolve(base.as_deref()).is_file() {
+                    a.data = None;
+                    changed = true;
+                } else {
+                    status = Some("Can't un-embed: source file is missing".to_owned());
+                }
+            }
+            if let Some(data) = &a.data {
+                ui.small(format!("{} bytes embedded", data.len()));
+            }
+
+            ui.add_space(4.0);
+            ui.strong("Preview");
+            if !a.exists(base.as_deref()) {
+                ui.colored_label(Color32::from_rgb(230, 160, 60), "File not found");
+            } else {
+                match a.kind {
+                    AssetKind::Image => {
+                        ui.add(
+                            egui::Image::new(Self::asset_uri(a, base.as_deref()))
+                                .max_size(vec2(240.0, 160.0)),
+                        );
+                    }
+                    AssetKind::Font => {
+                        let family = egui::FontFamily::Name(a.name.as_str().into());
+                        if ui.fonts(|f| f.families().contains(&family)) {
+                            ui.label(
+                                egui::RichText::new("The quick brown fox 0123")
+                                    .family(family)
+                                    .size(18.0),
+                            );
+                        } else {
+                            ui.weak("(loading font…)");
+                        }
+                    }
+                }
+            }
+            ui.add_space(6.0);
+            remove = ui.button("Remove").clicked();
+        }
+        if let Some(msg) = status {
+            self.set_status(msg);
+        }
+        if changed {
+            self.project.touch();
+        }
+        if remove {
+            self.remove_asset(id);
+        }
+    }
+
     fn top_bar(&mut self, ui: &mut egui::Ui) {
         // Show status message if recent
         if let Some((msg, time)) = &self.status_message {
@@ -1300,10 +2345,7 @@ impl RadBuilderApp {
                     .on_hover_text("Create a new empty project")
                     .clicked()
                 {
-                    self.project = Project::default();
-                    self.selected.clear();
-                    self.current_file = None;
-                    self.set_status("New project created".into());
+                    self.discard_then(Discard::New);
                     ui.close_kind(egui::UiKind::Menu);
                 }
                 ui.separator();
@@ -1312,28 +2354,16 @@ impl RadBuilderApp {
                     .on_hover_text("Open a project file (Ctrl+O)")
                     .clicked()
                 {
-                    if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("RAD Project", &["json", "rad"])
-                        .pick_file()
-                    {
-                        self.load_project(path);
-                    }
+                    self.discard_then(Discard::Open);
                     ui.close_kind(egui::UiKind::Menu);
                 }
+                ui.menu_button("Recent Projects", |ui| self.recent_menu(ui));
                 if ui
                     .button("Save")
                     .on_hover_text("Save project (Ctrl+S)")
                     .clicked()
                 {
-                    if let Some(path) = self.current_file.clone() {
-                        self.save_project(path);
-                    } else if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("RAD Project", &["json", "rad"])
-                        .set_file_name("project.json")
-                        .save_file()
-                    {
-                        self.save_project(path);
-                    }
+                    self.save();
                     ui.close_kind(egui::UiKind::Menu);
                 }
                 if ui
@@ -1356,7 +2386,17 @@ impl RadBuilderApp {
                     .on_hover_text("Generate Rust code (Ctrl+G)")
                     .clicked()
                 {
-                    self.generated = self.generate_code();
+                    self.generate_code();
+                    ui.close_kind(egui::UiKind::Menu);
+                }
+                if ui
+                    .button("Export Project...")
+                    .on_hover_text("Write Cargo.toml, src/main.rs and assets into a folder")
+                    .clicked()
+                {
+                    if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                        self.export_project(dir);
+                    }
                     ui.close_kind(egui::UiKind::Menu);
                 }
                 if ui
@@ -1366,6 +2406,7 @@ impl RadBuilderApp {
                 {
                     if let Ok(s) = serde_json::to_string_pretty(&self.project) {
                         self.generated = s;
+                        self.source_map = SourceMap::default();
                     }
                     ui.close_kind(egui::UiKind::Menu);
                 }
@@ -1374,9 +2415,33 @@ impl RadBuilderApp {
                     .on_hover_text("Import project from the editor below")
                     .clicked()
                 {
-                    if let Ok(p) = serde_json::from_str::<Project>(&self.generated) {
-                        self.project = p;
-                        self.selected.clear();
+                    match Project::from_json(&self.generated) {
+                        Ok(p) => {
+                            self.replace_project(p);
+                            self.selected.clear();
+                        }
+                        Err(e) => {
+                            self.set_status(format!("Import failed: {e}"));
+                            self.load_diagnostics = Some(LoadDiagnostics {
+                                file: None,
+                                json: self.generated.clone(),
+                                error: Some(e),
+                                problems: Vec::new(),
+                            });
+                        }
+                    }
+                    ui.close_kind(egui::UiKind::Menu);
+                }
+                if ui
+                    .button("Import Rust Code...")
+                    .on_hover_text("Rebuild a project from code this tool generated")
+                    .clicked()
+                {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Rust source", &["rs"])
+                        .pick_file()
+                    {
+                        self.import_rust(path);
                     }
                     ui.close_kind(egui::UiKind::Menu);
                 }
@@ -1394,6 +2459,7 @@ impl RadBuilderApp {
                     {
                         let to_delete: Vec<_> = self.selected.clone();
                         self.project.widgets.retain(|w| !to_delete.contains(&w.id));
+                        self.project.touch();
                         self.selected.clear();
                         ui.close_kind(egui::UiKind::Menu);
                     }
@@ -1443,6 +2509,26 @@ impl RadBuilderApp {
                     self.selected.clear();
                     ui.close_kind(egui::UiKind::Menu);
                 }
+                ui.separator();
+                if ui
+                    .button("Find in Code")
+                    .on_hover_text("Search the generated code (Ctrl+F)")
+                    .clicked()
+                {
+                    self.right_panel_tab = 1;
+                    self.code_find.open = true;
+                    self.code_find.focus = true;
+                    ui.close_kind(egui::UiKind::Menu);
+                }
+                if ui
+                    .button("Find in Project")
+                    .on_hover_text("Search and replace widget text (Ctrl+Shift+F)")
+                    .clicked()
+                {
+                    self.right_panel_tab = 3;
+                    self.find_focus = true;
+                    ui.close_kind(egui::UiKind::Menu);
+                }
             });
 
             // Alignment menu (only enabled with multi-select)
@@ -1547,9 +2633,12 @@ impl RadBuilderApp {
                 ui.checkbox(&mut self.show_grid, "Show Grid");
                 ui.checkbox(&mut self.syntax_highlighting, "Syntax Highlighting")
                     .on_hover_text("Enable syntax highlighting in code output");
+                self.theme_picker(ui);
                 ui.separator();
                 ui.checkbox(&mut self.preview_mode, "Preview Mode (F5)")
-                    .on_hover_text("Toggle preview mode: interact with widgets without selection handles");
+                    .on_hover_text(
+                        "Toggle preview mode: interact with widgets without selection handles",
+                    );
             });
 
             ui.menu_button("Settings", |ui| {
@@ -1559,16 +2648,25 @@ impl RadBuilderApp {
                 });
                 ui.horizontal(|ui| {
                     ui.label("Canvas size");
-                    ui.add(egui::DragValue::new(&mut self.project.canvas_size.x));
-                    ui.add(egui::DragValue::new(&mut self.project.canvas_size.y));
+                    let mut size = self.project.canvas_size;
+                    let rx = ui.add(egui::DragValue::new(&mut size.x).range(100.0..=4000.0));
+                    let ry = ui.add(egui::DragValue::new(&mut size.y).range(100.0..=4000.0));
+                    if rx.changed() || ry.changed() {
+                        self.resize_canvas(size);
+                    }
                 });
                 ui.separator();
                 ui.strong("Panels");
                 ui.add_space(4.0);
-                ui.checkbox(&mut self.project.panel_top_enabled, "Top");
-                ui.checkbox(&mut self.project.panel_bottom_enabled, "Bottom");
-                ui.checkbox(&mut self.project.panel_left_enabled, "Left");
-                ui.checkbox(&mut self.project.panel_right_enabled, "Right");
+                let panels = [
+                    ui.checkbox(&mut self.project.panel_top_enabled, "Top"),
+                    ui.checkbox(&mut self.project.panel_bottom_enabled, "Bottom"),
+                    ui.checkbox(&mut self.project.panel_left_enabled, "Left"),
+                    ui.checkbox(&mut self.project.panel_right_enabled, "Right"),
+                ];
+                if panels.iter().any(|r| r.changed()) {
+                    self.project.touch();
+                }
                 ui.separator();
                 ui.strong("Code Generation");
                 ui.add_space(4.0);
@@ -1576,6 +2674,11 @@ impl RadBuilderApp {
                     .on_hover_text("Automatically regenerate code when widgets change");
                 ui.checkbox(&mut self.codegen_comments, "Include comments")
                     .on_hover_text("Add explanatory comments to generated code");
+                ui.checkbox(&mut self.codegen_web, "Web entry point (trunk)")
+                    .on_hover_text(
+                        "Add a wasm32 main using eframe::WebRunner; Export Project \
+                         also writes index.html and the wasm-only dependencies",
+                    );
                 ui.horizontal(|ui| {
                     ui.label("Output format:");
                     egui::ComboBox::from_id_salt("codegen_format")
@@ -1596,13 +2699,73 @@ impl RadBuilderApp {
                                 CodeGenFormat::UiOnly,
                                 "UI Function Only",
                             );
+                            ui.selectable_value(
+                                &mut self.codegen_format,
+                                CodeGenFormat::Component,
+                                "Component Struct",
+                            );
+                            ui.selectable_value(
+                                &mut self.codegen_format,
+                                CodeGenFormat::Bevy,
+                                "Bevy Plugin",
+                            );
                         });
                 });
+                if self.codegen_format == CodeGenFormat::Component {
+                    ui.horizontal(|ui| {
+                        ui.label("Struct name:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.codegen_component.name)
+                                .desired_width(120.0),
+                        );
+                    });
+                    if self.codegen_component.ident().is_none() {
+                        ui.colored_label(
+                            Color32::from_rgb(230, 160, 60),
+                            "Not a valid identifier; MyPanel is used instead",
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Visibility:");
+                        egui::ComboBox::from_id_salt("codegen_visibility")
+                            .selected_text(self.codegen_component.visibility.display_name())
+                            .show_ui(ui, |ui| {
+                                for vis in
+                                    [Visibility::Public, Visibility::Crate, Visibility::Private]
+                                {
+                                    ui.selectable_value(
+                                        &mut self.codegen_component.visibility,
+                                        vis,
+                                        vis.display_name(),
+                                    );
+                                }
+                            });
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Layout:");
+                    egui::ComboBox::from_id_salt("codegen_layout")
+                        .selected_text(self.codegen_layout.display_name())
+                        .show_ui(ui, |ui| {
+                            for layout in [CodeGenLayout::Absolute, CodeGenLayout::Flow] {
+                                ui.selectable_value(
+                                    &mut self.codegen_layout,
+                                    layout,
+                                    layout.display_name(),
+                                );
+                            }
+                        });
+                })
+                .response
+                .on_hover_text(
+                    "Flow infers ui.horizontal / egui::Grid from widget positions; \
+                     widgets it can't fit stay absolutely placed",
+                );
             });
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui.button("Generate Code").on_hover_text("Ctrl+G").clicked() {
-                    self.generated = self.generate_code();
+                    self.generate_code();
                 }
                 // Preview/Edit mode toggle button
                 ui.separator();
@@ -1649,6 +2812,7 @@ impl RadBuilderApp {
                 w.pos.x = min_x;
             }
         }
+        self.project.touch();
     }
 
     fn align_right(&mut self) {
@@ -1666,6 +2830,7 @@ impl RadBuilderApp {
                 w.pos.x = max_right - w.size.x;
             }
         }
+        self.project.touch();
     }
 
     fn align_center_h(&mut self) {
@@ -1684,6 +2849,7 @@ impl RadBuilderApp {
                 w.pos.x = avg_center - w.size.x / 2.0;
             }
         }
+        self.project.touch();
     }
 
     fn align_top(&mut self) {
@@ -1701,6 +2867,7 @@ impl RadBuilderApp {
                 w.pos.y = min_y;
             }
         }
+        self.project.touch();
     }
 
     fn align_bottom(&mut self) {
@@ -1718,6 +2885,7 @@ impl RadBuilderApp {
                 w.pos.y = max_bottom - w.size.y;
             }
         }
+        self.project.touch();
     }
 
     fn align_center_v(&mut self) {
@@ -1736,6 +2904,7 @@ impl RadBuilderApp {
                 w.pos.y = avg_center - w.size.y / 2.0;
             }
         }
+        self.project.touch();
     }
 
     fn distribute_horizontal(&mut self) {
@@ -1762,6 +2931,7 @@ impl RadBuilderApp {
             }
             x += width + spacing;
         }
+        self.project.touch();
     }
 
     fn distribute_vertical(&mut self) {
@@ -1788,6 +2958,7 @@ impl RadBuilderApp {
             }
             y += height + spacing;
         }
+        self.project.touch();
     }
 
     fn match_width(&mut self) {
@@ -1806,6 +2977,7 @@ impl RadBuilderApp {
                 w.size.x = target_width;
             }
         }
+        self.project.touch();
     }
 
     fn match_height(&mut self) {
@@ -1824,6 +2996,7 @@ impl RadBuilderApp {
                 w.size.y = target_height;
             }
         }
+        self.project.touch();
     }
 
     fn generated_panel(&mut self, ui: &mut egui::Ui) {
@@ -1837,985 +3010,831 @@ impl RadBuilderApp {
             });
         });
         ui.label("Rust code (or JSON export) will appear here. Copy-paste into your app.");
+        if self.codegen_layout == CodeGenLayout::Flow {
+            let fallbacks = codegen::flow_fallbacks(&self.project);
+            if !fallbacks.is_empty() {
+                ui.colored_label(
+                    Color32::from_rgb(230, 180, 80),
+                    format!(
+                        "⚠ Flow layout kept absolute placement for {} widget(s):",
+                        fallbacks.len()
+                    ),
+                );
+                for (w, reason) in fallbacks {
+                    ui.label(format!(
+                        "  #{} {} ({:?}): {}",
+                        w.id,
+                        w.kind.display_name(),
+                        w.area,
+                        reason.reason()
+                    ));
+                }
+            }
+        }
+
+        if !self.import_warnings.is_empty() {
+            ui.colored_label(
+                Color32::from_rgb(230, 180, 80),
+                format!(
+                    "⚠ Import skipped {} construct(s):",
+                    self.import_warnings.len()
+                ),
+            );
+            for warning in &self.import_warnings {
+                ui.label(format!("  {warning}"));
+            }
+        }
+
+        self.diff_bar(ui);
+        if self.show_diff {
+            self.diff_view(ui);
+            return;
+        }
+
+        let matches = if self.code_find.open {
+            let find = &self.code_find;
+            search::find_all(&self.generated, &find.query, find.match_case)
+        } else {
+            Vec::new()
+        };
+        if self.code_find.open {
+            self.code_find_bar(ui, matches.len());
+        }
+        let current_match = matches.get(self.code_find.current).cloned();
 
         // A scrollable viewport for the generated text:
-        egui::ScrollArea::vertical()
+        egui::ScrollArea::both()
             .id_salt("generated_output_scroll")
             .max_height(280.0)
             .auto_shrink([false, false])
             .show(ui, |ui| {
-                if self.syntax_highlighting && !self.generated.is_empty() {
-                    // Display with syntax highlighting (read-only view)
-                    let job = self.highlighter.layout_job(&self.generated);
-                    ui.add(egui::Label::new(job).selectable(true));
+                // the selected widget's lines, scrolled to when the selection changes
+                let selected = self.selected.first().copied();
+                let span = selected.and_then(|id| self.source_map.span(id));
+                let output = if self.syntax_highlighting {
+                    // selected widget, then search matches, the current one brighter
+                    let visuals = ui.visuals();
+                    let fill = visuals.selection.bg_fill.gamma_multiply(0.5);
+                    let found = visuals.warn_fg_color.gamma_multiply(0.35);
+                    let current = visuals.warn_fg_color.gamma_multiply(0.7);
+                    let tints: Vec<_> = span
+                        .clone()
+                        .map(|span| (span, fill))
+                        .into_iter()
+                        .chain(matches.iter().map(|m| (m.clone(), found)))
+                        .chain(current_match.clone().map(|m| (m, current)))
+                        .collect();
+                    highlight::code_editor_highlighted(
+                        ui,
+                        &mut self.highlighter,
+                        &mut self.generated,
+                        &tints,
+                    )
                 } else {
-                    // Plain text editor (editable)
-                    let editor = egui::TextEdit::multiline(&mut self.generated)
+                    // Plain text editor, for very large output
+                    egui::TextEdit::multiline(&mut self.generated)
                         .code_editor()
                         .lock_focus(true)
                         .desired_rows(18)
-                        .desired_width(f32::INFINITY);
-                    ui.add(editor);
+                        .desired_width(f32::INFINITY)
+                        .show(ui)
+                };
+                if output.response.changed() {
+                    self.source_map = SourceMap::default();
+                }
+                let clicked_at = output
+                    .cursor_range
+                    .filter(|_| output.response.clicked())
+                    .map(|r| r.primary.index);
+                let (galley, galley_pos) = (output.galley, output.galley_pos);
+                let char_at = |byte: usize| self.generated[..byte].chars().count();
+
+                if let Some(found) = current_match.filter(|_| self.code_find.jump) {
+                    // select the match, so it's also visible without highlighting
+                    let (start, end) = (char_at(found.start), char_at(found.end));
+                    let mut state = output.state;
+                    state
+                        .cursor
+                        .set_char_range(Some(egui::text::CCursorRange::two(
+                            egui::text::CCursor::new(start),
+                            egui::text::CCursor::new(end),
+                        )));
+                    state.store(ui.ctx(), output.response.id);
+                    let rect = galley
+                        .pos_from_cursor(egui::text::CCursor::new(start))
+                        .union(galley.pos_from_cursor(egui::text::CCursor::new(end)));
+                    ui.scroll_to_rect(
+                        rect.translate(galley_pos.to_vec2()),
+                        Some(egui::Align::Center),
+                    );
+                    self.code_find.jump = false;
+                } else if let Some(at) = clicked_at {
+                    let byte = self
+                        .generated
+                        .char_indices()
+                        .nth(at)
+                        .map_or(self.generated.len(), |(b, _)| b);
+                    if let Some(id) = self.source_map.widget_at(byte) {
+                        self.selected = vec![id];
+                        self.code_scrolled_to = Some(id);
+                    }
+                } else if let Some(span) = span.filter(|_| self.code_scrolled_to != selected) {
+                    let (start, end) = (char_at(span.start), char_at(span.end));
+                    let rect = galley
+                        .pos_from_cursor(egui::text::CCursor::new(start))
+                        .union(galley.pos_from_cursor(egui::text::CCursor::new(end)));
+                    ui.scroll_to_rect(
+                        rect.translate(galley_pos.to_vec2()),
+                        Some(egui::Align::Center),
+                    );
+                    self.code_scrolled_to = selected;
+                } else if selected.is_none() {
+                    self.code_scrolled_to = None;
                 }
             });
     }
 
-    fn generate_code(&self) -> String {
-        match self.codegen_format {
-            CodeGenFormat::SingleFile => self.generate_single_file(),
-            CodeGenFormat::SeparateFiles => self.generate_separate_files(),
-            CodeGenFormat::UiOnly => self.generate_ui_only(),
+    /// Turns the diff view on and picks what it compares against
+    fn diff_bar(&mut self, ui: &mut egui::Ui) {
+        let mut choose_file = false;
+        let mut reload = None;
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_diff, "Show Changes")
+                .on_hover_text("Compare the output with the previous output or a file");
+            if !self.show_diff {
+                return;
+            }
+            ui.label("against");
+            let current = match &self.diff_base {
+                DiffBase::Previous => "previous output".to_owned(),
+                DiffBase::File(path, _) => path.display().to_string(),
+            };
+            egui::ComboBox::from_id_salt("diff_base")
+                .selected_text(current)
+                .show_ui(ui, |ui| {
+                    let previous = matches!(self.diff_base, DiffBase::Previous);
+                    if ui.selectable_label(previous, "previous output").clicked() {
+                        self.diff_base = DiffBase::Previous;
+                    }
+                    choose_file = ui.selectable_label(!previous, "file...").clicked();
+                });
+            if let DiffBase::File(path, _) = &self.diff_base
+                && ui.button("⟳").on_hover_text("Reload the file").clicked()
+            {
+                reload = Some(path.clone());
+            }
+        });
+        if choose_file {
+            reload = rfd::FileDialog::new()
+                .add_filter("Rust or JSON", &["rs", "json"])
+                .pick_file();
+        }
+        if let Some(path) = reload {
+            match std::fs::read_to_string(&path) {
+                Ok(text) => self.diff_base = DiffBase::File(path, text),
+                Err(e) => self.set_status(format!("Failed to read {}: {e}", path.display())),
+            }
         }
     }
 
-    /// Generate all code in a single file
-    fn generate_single_file(&self) -> String {
-        use DockArea::*;
-        let mut out = String::new();
-
-        // Header comment
-        if self.codegen_comments {
-            out.push_str("// =============================================================================\n");
-            out.push_str("// Generated by egui RAD GUI Builder\n");
-            out.push_str("// https://github.com/timschmidt/egui-rad-builder\n");
-            out.push_str("// =============================================================================\n\n");
-        } else {
-            out.push_str("// --- generated by egui RAD GUI Builder ---\n");
+    /// Line diff of the output against `diff_base`, with a patch to copy
+    fn diff_view(&mut self, ui: &mut egui::Ui) {
+        let (base, name) = match &self.diff_base {
+            DiffBase::Previous => (&self.previous_generated, None),
+            DiffBase::File(path, text) => (text, path.file_name()),
+        };
+        let (old_name, new_name) = match name {
+            Some(name) => {
+                let name = name.to_string_lossy();
+                (format!("a/{name}"), format!("b/{name}"))
+            }
+            None => ("previous".to_owned(), "current".to_owned()),
+        };
+        let mut hasher = DefaultHasher::new();
+        (base, &self.generated, &old_name).hash(&mut hasher);
+        let key = hasher.finish();
+        if self.diff_cache.as_ref().is_none_or(|(k, _)| *k != key) {
+            let diff = Diff::new(base, &self.generated, &old_name, &new_name);
+            self.diff_cache = Some((key, diff));
         }
+        let Some((_, diff)) = &self.diff_cache else {
+            return;
+        };
 
-        out.push_str("use eframe::egui;\n");
-        out.push_str("use egui_extras::DatePickerButton;\n");
-        out.push_str("use chrono::NaiveDate;\n\n");
+        let added = Color32::from_rgba_unmultiplied(60, 170, 60, 60);
+        let removed = Color32::from_rgba_unmultiplied(210, 60, 60, 60);
+        let mut copied = false;
+        ui.horizontal(|ui| {
+            ui.colored_label(Color32::from_rgb(80, 180, 80), format!("+{}", diff.added));
+            ui.colored_label(Color32::from_rgb(220, 90, 90), format!("-{}", diff.removed));
+            if ui
+                .add_enabled(!diff.is_empty(), egui::Button::new("Copy Patch"))
+                .on_hover_text("Copy the changes as a unified diff")
+                .clicked()
+            {
+                ui.ctx().copy_text(diff.patch.clone());
+                copied = true;
+            }
+        });
+        if diff.is_empty() {
+            ui.weak("No changes");
+            return;
+        }
 
-        let has_tree = self
-            .project
-            .widgets
+        // hunk headers, then their lines
+        let rows: Vec<(Option<ChangeTag>, &str)> = diff
+            .hunks
             .iter()
-            .any(|w| matches!(w.kind, WidgetKind::Tree));
-        if has_tree {
-            out.push_str(
-                "#[derive(Clone)]\n\
-				 struct GenTreeNode { label: String, children: Vec<GenTreeNode> }\n\
-				 \n\
-				 fn gen_show_tree(ui: &mut egui::Ui, nodes: &[GenTreeNode]) {\n\
-				 \tfor n in nodes {\n\
-				 \t\tif n.children.is_empty() { ui.label(&n.label); }\n\
-				 \t\telse { ui.collapsing(&n.label, |ui| gen_show_tree(ui, &n.children)); }\n\
-				 \t}\n\
-				 }\n\n",
-            );
+            .flat_map(|hunk| {
+                std::iter::once((None, hunk.header.as_str())).chain(
+                    hunk.lines
+                        .iter()
+                        .map(|(tag, line)| (Some(*tag), line.as_str())),
+                )
+            })
+            .collect();
+        let font = egui::FontId::monospace(highlight::CODE_FONT_SIZE);
+        let row_height = ui.fonts_mut(|f| f.row_height(&font));
+        egui::ScrollArea::both()
+            .id_salt("diff_scroll")
+            .max_height(280.0)
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, rows.len(), |ui, range| {
+                ui.spacing_mut().item_spacing.y = 0.0;
+                for &(tag, line) in &rows[range] {
+                    let text = match tag {
+                        None => egui::RichText::new(line).weak(),
+                        Some(ChangeTag::Insert) => {
+                            egui::RichText::new(format!("+{line}")).background_color(added)
+                        }
+                        Some(ChangeTag::Delete) => {
+                            egui::RichText::new(format!("-{line}")).background_color(removed)
+                        }
+                        Some(ChangeTag::Equal) => egui::RichText::new(format!(" {line}")),
+                    };
+                    ui.add(egui::Label::new(text.font(font.clone())).extend());
+                }
+            });
+        if copied {
+            self.set_status("Patch copied to the clipboard".into());
+        }
+    }
+
+    /// Search box, match count and navigation above the generated code
+    fn code_find_bar(&mut self, ui: &mut egui::Ui, matches: usize) {
+        let find = &mut self.code_find;
+        if find.current >= matches {
+            find.current = 0;
         }
+        ui.horizontal(|ui| {
+            ui.label("Find:");
+            let edit = ui.add(
+                egui::TextEdit::singleline(&mut find.query)
+                    .desired_width(140.0)
+                    .hint_text("text in the code"),
+            );
+            if std::mem::take(&mut find.focus) {
+                edit.request_focus();
+            }
+            if edit.changed() {
+                find.current = 0;
+                find.jump = true;
+            }
+            let (enter, shift, escape) = ui.input(|i| {
+                (
+                    i.key_pressed(egui::Key::Enter),
+                    i.modifiers.shift,
+                    i.key_pressed(egui::Key::Escape),
+                )
+            });
+            if edit.lost_focus() && enter {
+                find.step(if shift { -1 } else { 1 }, matches);
+                edit.request_focus();
+            }
+            if ui
+                .checkbox(&mut find.match_case, "Aa")
+                .on_hover_text("Match case")
+                .changed()
+            {
+                find.current = 0;
+                find.jump = true;
+            }
+            if ui
+                .add_enabled(matches > 0, egui::Button::new("⏶"))
+                .on_hover_text("Previous match (Shift+Enter)")
+                .clicked()
+            {
+                find.step(-1, matches);
+            }
+            if ui
+                .add_enabled(matches > 0, egui::Button::new("⏷"))
+                .on_hover_text("Next match (Enter)")
+                .clicked()
+            {
+                find.step(1, matches);
+            }
+            if matches > 0 {
+                ui.label(format!("{} of {matches}", find.current + 1));
+            } else if !find.query.is_empty() {
+                ui.weak("No matches");
+            }
+            if ui.button("✖").on_hover_text("Close (Esc)").clicked()
+                || (escape && edit.lost_focus())
+            {
+                find.open = false;
+            }
+        });
+    }
 
-        out.push_str("struct GeneratedState {\n");
-        out.push_str(
-            "    enable_top: bool, enable_bottom: bool, enable_left: bool, enable_right: bool,\n",
+    /// Project-wide search over widget text, items, URLs and tooltips,
+    /// with replace-all
+    fn find_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Find in Project");
+        ui.label("Searches widget text, list items, URLs and tooltips.");
+        let edit = ui.add(
+            egui::TextEdit::singleline(&mut self.find_query)
+                .hint_text("Find")
+                .desired_width(f32::INFINITY),
         );
-        for w in &self.project.widgets {
-            match w.kind {
-                WidgetKind::TextEdit => out.push_str(&format!("    text_{}: String,\n", w.id)),
-                WidgetKind::Checkbox => out.push_str(&format!("    checked_{}: bool,\n", w.id)),
-                WidgetKind::Slider => out.push_str(&format!("    value_{}: f32,\n", w.id)),
-                WidgetKind::ProgressBar => out.push_str(&format!("    progress_{}: f32,\n", w.id)),
-                WidgetKind::SelectableLabel => out.push_str(&format!("    sel_{}: bool,\n", w.id)),
-                WidgetKind::RadioGroup | WidgetKind::ComboBox | WidgetKind::MenuButton => {
-                    out.push_str(&format!("    sel_{}: usize,\n", w.id))
-                }
-                WidgetKind::CollapsingHeader => {
-                    out.push_str(&format!("    open_{}: bool,\n", w.id))
-                }
-                WidgetKind::DatePicker => out.push_str(&format!("    date_{}: NaiveDate,\n", w.id)),
-                WidgetKind::Password => out.push_str(&format!("    pass_{}: String,\n", w.id)),
-                WidgetKind::AngleSelector => out.push_str(&format!("    angle_{}: f32,\n", w.id)),
-                WidgetKind::TextArea => out.push_str(&format!("    textarea_{}: String,\n", w.id)),
-                WidgetKind::DragValue => out.push_str(&format!("    drag_{}: f32,\n", w.id)),
-                WidgetKind::ColorPicker => {
-                    out.push_str(&format!("    color_{}: egui::Color32,\n", w.id))
-                }
-                WidgetKind::Code => out.push_str(&format!("    code_{}: String,\n", w.id)),
-                _ => {}
+        if std::mem::take(&mut self.find_focus) {
+            edit.request_focus();
+        }
+        ui.add(
+            egui::TextEdit::singleline(&mut self.find_replacement)
+                .hint_text("Replace with")
+                .desired_width(f32::INFINITY),
+        );
+        let hits = search::search_project(&self.project, &self.find_query, self.find_match_case);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.find_match_case, "Match case");
+            if ui
+                .add_enabled(!hits.is_empty(), egui::Button::new("Replace All"))
+                .on_hover_text("Replace every match in the listed widgets")
+                .clicked()
+            {
+                let count = search::replace_in_project(
+                    &mut self.project,
+                    &self.find_query,
+                    &self.find_replacement,
+                    self.find_match_case,
+                );
+                if count > 0 {
+                    self.project.touch();
+                }
+                self.set_status(format!("Replaced {count} occurrence(s)"));
             }
+        });
+        ui.separator();
+        if self.find_query.is_empty() {
+            return;
+        }
+        if hits.is_empty() {
+            ui.weak("No matches");
+            return;
         }
-        out.push_str("}\n\n");
+        ui.label(format!("{} match(es):", hits.len()));
+        egui::ScrollArea::vertical()
+            .id_salt("find_results_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for hit in &hits {
+                    let label = format!(
+                        "#{} {} ({}): {}",
+                        hit.widget,
+                        hit.kind.display_name(),
+                        hit.field.describe(),
+                        hit.value
+                    );
+                    if ui
+                        .selectable_label(self.is_selected(hit.widget), label)
+                        .clicked()
+                    {
+                        self.select_single(hit.widget);
+                    }
+                }
+            });
+    }
 
-        out.push_str("impl Default for GeneratedState {\n");
-        out.push_str("    fn default() -> Self {\n");
-        out.push_str("        Self {\n");
-        out.push_str(&format!(
-            "            enable_top: {}, enable_bottom: {}, enable_left: {}, enable_right: {},\n",
-            if self.project.panel_top_enabled {
-                "true"
-            } else {
-                "false"
-            },
-            if self.project.panel_bottom_enabled {
-                "true"
-            } else {
-                "false"
-            },
-            if self.project.panel_left_enabled {
-                "true"
-            } else {
-                "false"
-            },
-            if self.project.panel_right_enabled {
-                "true"
-            } else {
-                "false"
-            },
+    /// Design problems from `lint::lint`; clicking one selects its widget
+    fn problems_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Problems");
+        let panels = [
+            (DockArea::Top, self.live_top),
+            (DockArea::Bottom, self.live_bottom),
+            (DockArea::Left, self.live_left),
+            (DockArea::Right, self.live_right),
+        ];
+        let panel_size = |area| {
+            panels
+                .iter()
+                .find(|(a, _)| *a == area)
+                .and_then(|(_, rect)| rect.map(|r| r.size()))
+        };
+        let base = self.project_dir();
+        // the canvas previews the design in the builder's own theme
+        let issues = lint::lint(&self.project, base.as_deref(), ui.visuals(), &panel_size);
+        if issues.is_empty() {
+            ui.label("No problems found.");
+            return;
+        }
+        let errors = issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .count();
+        ui.label(format!(
+            "{errors} error(s), {} warning(s)",
+            issues.len() - errors
         ));
-
-        for w in &self.project.widgets {
-            match w.kind {
-                WidgetKind::TextEdit => {
-                    out.push_str(&format!(
-                        "            text_{}: \"{}\".to_owned(),\n",
-                        w.id,
-                        widget::escape(&w.props.text)
-                    ));
-                }
-                WidgetKind::Checkbox => {
-                    out.push_str(&format!(
-                        "            checked_{}: {},\n",
-                        w.id,
-                        if w.props.checked { "true" } else { "false" }
-                    ));
-                }
-                WidgetKind::Slider => {
-                    out.push_str(&format!(
-                        "            value_{}: {:.3},\n",
-                        w.id, w.props.value
-                    ));
-                }
-                WidgetKind::ProgressBar => {
-                    let p = w.props.value.clamp(0.0, 1.0);
-                    out.push_str(&format!("            progress_{}: {:.3},\n", w.id, p));
-                }
-                WidgetKind::SelectableLabel => {
-                    out.push_str(&format!(
-                        "            sel_{}: {},\n",
-                        w.id,
-                        if w.props.checked { "true" } else { "false" }
-                    ));
-                }
-                WidgetKind::RadioGroup | WidgetKind::ComboBox | WidgetKind::MenuButton => {
-                    let sel = if w.props.items.is_empty() {
-                        0
-                    } else {
-                        w.props.selected.min(w.props.items.len() - 1)
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .id_salt("problems_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for issue in &issues {
+                    let (icon, color) = match issue.severity {
+                        Severity::Error => ("⛔", ui.visuals().error_fg_color),
+                        Severity::Warning => ("⚠", ui.visuals().warn_fg_color),
                     };
-                    out.push_str(&format!("            sel_{}: {},\n", w.id, sel));
-                }
-                WidgetKind::CollapsingHeader => {
-                    out.push_str(&format!(
-                        "            open_{}: {},\n",
-                        w.id,
-                        if w.props.checked { "true" } else { "false" }
-                    ));
-                }
-                WidgetKind::DatePicker => {
-                    let y = w.props.year;
-                    let m = w.props.month.clamp(1, 12);
-                    let d = w.props.day.clamp(1, 28);
-                    out.push_str(&format!(
-                        "            date_{}: NaiveDate::from_ymd_opt({}, {}, {}).unwrap(),\n",
-                        w.id, y, m, d
-                    ));
-                }
-                WidgetKind::Password => {
-                    out.push_str(&format!(
-                        "            pass_{}: \"{}\".to_owned(),\n",
-                        w.id,
-                        widget::escape(&w.props.text)
-                    ));
-                }
-                WidgetKind::AngleSelector => {
-                    out.push_str(&format!(
-                        "            angle_{}: {:.3},\n",
-                        w.id, w.props.value
-                    ));
-                }
-                WidgetKind::TextArea => {
-                    out.push_str(&format!(
-                        "            textarea_{}: \"{}\".to_owned(),\n",
-                        w.id,
-                        widget::escape(&w.props.text)
-                    ));
-                }
-                WidgetKind::DragValue => {
-                    out.push_str(&format!(
-                        "            drag_{}: {:.3},\n",
-                        w.id, w.props.value
-                    ));
-                }
-                WidgetKind::ColorPicker => {
-                    out.push_str(&format!(
-                        "            color_{}: egui::Color32::from_rgba_unmultiplied({}, {}, {}, {}),\n",
-                        w.id, w.props.color[0], w.props.color[1], w.props.color[2], w.props.color[3]
-                    ));
-                }
-                WidgetKind::Code => {
-                    out.push_str(&format!(
-                        "            code_{}: \"{}\".to_owned(),\n",
-                        w.id,
-                        widget::escape(&w.props.text)
-                    ));
+                    let kind = self
+                        .project
+                        .widgets
+                        .iter()
+                        .find(|w| w.id == issue.widget)
+                        .map_or("", |w| w.kind.display_name());
+                    ui.horizontal(|ui| {
+                        ui.colored_label(color, icon);
+                        let label = format!("#{} {kind} {}", issue.widget, issue.message);
+                        if ui
+                            .selectable_label(self.is_selected(issue.widget), label)
+                            .clicked()
+                        {
+                            self.select_single(issue.widget);
+                        }
+                    });
                 }
-                _ => {}
-            }
-        }
-        out.push_str("        }\n");
-        out.push_str("    }\n");
-        out.push_str("}\n\n");
+            });
+    }
 
-        // helper macro to emit a widget block at rect (origin + local pos)
-        let emit_widget = |w: &Widget, out: &mut String, origin: &str| {
-            let pos = w.pos;
-            let size = w.size;
-            match w.kind {
-				WidgetKind::MenuButton=>{
-					let items_code = if w.props.items.is_empty() {
-						"\"Item\".to_string()".to_owned()
-					} else {
-						w.props.items.iter().map(|s| format!("\"{}\".to_string()", escape(s))).collect::<Vec<_>>().join(", ")
-					};
-					out.push_str(&format!(
-						"    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{\n",
-						x=w.pos.x, y=w.pos.y, w=w.size.x, h=w.size.y
-					));
-					out.push_str(&format!("        let items = vec![{items}];\n", items=items_code));
-					out.push_str(&format!(
-						"        ui.menu_button(\"{}\", |ui| {{\n", escape(&w.props.text)
-					));
-					out.push_str(&format!(
-						"            for (i, it) in items.iter().enumerate() {{ if ui.button(it).clicked() {{ state.sel_{id} = i; ui.close_kind(egui::UiKind::Menu); }} }}\n",
-						id = w.id
-					));
-					out.push_str("        });\n");
-					out.push_str("    });\n");
-				}
-                WidgetKind::Label => out.push_str(&format!(
-                    "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.label(\"{}\"); }});\n",
-                    pos.x,pos.y,size.x,size.y,escape(&w.props.text)
-                )),
-                WidgetKind::Small => out.push_str(&format!(
-                    "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.small(\"{}\"); }});\n",
-                    pos.x,pos.y,size.x,size.y,escape(&w.props.text)
-                )),
-                WidgetKind::Monospace => out.push_str(&format!(
-                    "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.monospace(\"{}\"); }});\n",
-                    pos.x,pos.y,size.x,size.y,escape(&w.props.text)
-                )),
-                WidgetKind::Button => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.add_sized(egui::vec2({:.1},{:.1}), egui::Button::new(\"{}\")); }});\n",
-                        pos.x, pos.y, size.x, size.y, size.x, size.y, escape(&w.props.text)
-                    ));
-                }
-                WidgetKind::ImageTextButton => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-							{origin} + egui::vec2({x:.1},{y:.1}), \
-							egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-							ui.add_sized(egui::vec2({w:.1},{h:.1}), \
-								egui::Button::new(format!(\"{{}}  {{}}\", \"{icon}\", \"{text}\")) \
-							); \
-						}});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        icon = escape(&w.props.icon),
-                        text = escape(&w.props.text),
-                    ));
-                }
-                WidgetKind::Checkbox => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.checkbox(&mut state.checked_{}, \"{}\"); }});\n",
-                        pos.x, pos.y, size.x, size.y, w.id, escape(&w.props.text)
-                    ));
-                }
-                WidgetKind::TextEdit => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.add_sized(egui::vec2({:.1},{:.1}), egui::TextEdit::singleline(&mut state.text_{}).hint_text(\"{}\")); }});\n",
-                        pos.x, pos.y, size.x, size.y, size.x, size.y, w.id, escape(&w.props.text)
-                    ));
-                }
-                WidgetKind::Slider => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.add_sized(egui::vec2({:.1},{:.1}), egui::Slider::new(&mut state.value_{}, {:.3}..={:.3}).text(\"{}\")); }});\n",
-                        pos.x, pos.y, size.x, size.y, size.x, size.y, w.id, w.props.min, w.props.max, escape(&w.props.text)
-                    ));
-                }
-                WidgetKind::ProgressBar => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.add_sized(egui::vec2({:.1},{:.1}), egui::ProgressBar::new(state.progress_{}).show_percentage()); }});\n",
-                        pos.x, pos.y, size.x, size.y, size.x, size.y, w.id
-                    ));
-                }
-                WidgetKind::RadioGroup => {
-                    let items_code = if w.props.items.is_empty() {
-                        "\"Item\".to_string()".to_owned()
-                    } else {
-                        w.props
-                            .items
-                            .iter()
-                            .map(|s| format!("\"{}\".to_string()", escape(s)))
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    };
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{\n",
-                        pos.x, pos.y, size.x, size.y
-                    ));
-                    out.push_str(&format!("        let items = vec![{}];\n", items_code));
-                    out.push_str(&format!(
-                        "        for (i, it) in items.iter().enumerate() {{ if ui.add(egui::RadioButton::new(state.sel_{} == i, it)).clicked() {{ state.sel_{} = i; }} }}\n",
-                        w.id, w.id
-                    ));
-                    out.push_str("    });\n");
-                }
-                WidgetKind::Link => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.link(\"{}\"); }});\n",
-                        pos.x, pos.y, size.x, size.y, escape(&w.props.text)
-                    ));
-                }
-                WidgetKind::Hyperlink => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.hyperlink_to(\"{}\", \"{}\"); }});\n",
-                        pos.x, pos.y, size.x, size.y, escape(&w.props.text), escape(&w.props.url)
-                    ));
-                }
-                WidgetKind::SelectableLabel => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ if ui.add(egui::Button::selectable(state.sel_{}, \"{}\")).clicked() {{ state.sel_{} = !state.sel_{}; }} }});\n",
-                        pos.x, pos.y, size.x, size.y, w.id, escape(&w.props.text), w.id, w.id
-                    ));
-                }
-                WidgetKind::ComboBox => {
-                    let items_code = if w.props.items.is_empty() {
-                        "\"Item\".to_string()".to_owned()
-                    } else {
-                        w.props
-                            .items
-                            .iter()
-                            .map(|s| format!("\"{}\".to_string()", escape(s)))
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    };
+    fn codegen_context(&self) -> GenContext<'_> {
+        GenContext {
+            project: &self.project,
+            layout: self.codegen_layout,
+            comments: self.codegen_comments,
+            web: self.codegen_web,
+            component: &self.codegen_component,
+        }
+    }
 
-                    out.push_str(&format!(
-						"    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{\n",
-						x = pos.x, y = pos.y, w = size.x, h = size.y
-					));
-                    out.push_str(&format!(
-                        "        let items = vec![{items}];\n",
-                        items = items_code
-                    ));
-                    out.push_str(&format!(
-                        "        egui::ComboBox::from_id_source({id})\n",
-                        id = w.id
-                    ));
-                    out.push_str(&format!("            .width({:.1})\n", size.x));
-                    out.push_str(&format!(
-						"            .selected_text(items.get(state.sel_{id}).cloned().unwrap_or_else(|| \"\".to_string()))\n",
-						id = w.id
-					));
-                    out.push_str("            .show_ui(ui, |ui| {\n");
-                    out.push_str(&format!(
-						"                for (i, it) in items.iter().enumerate() {{ ui.selectable_value(&mut state.sel_{id}, i, it.clone()); }}\n",
-						id = w.id
-					));
-                    out.push_str("            });\n");
-                    out.push_str("    });\n");
-                }
-                WidgetKind::Separator => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.separator(); }});\n",
-                        pos.x, pos.y, size.x, size.y
-                    ));
-                }
-                WidgetKind::CollapsingHeader => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ egui::CollapsingHeader::new(\"{}\").default_open(state.open_{}).show(ui, |ui| {{ ui.label(\"… place your inner content here …\"); }}); }});\n",
-                        pos.x, pos.y, size.x, size.y, escape(&w.props.text), w.id
-                    ));
-                }
-                WidgetKind::DatePicker => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size({origin} + egui::vec2({:.1},{:.1}), egui::vec2({:.1},{:.1}))), |ui| {{ ui.horizontal(|ui| {{ ui.label(\"{}\"); ui.add(DatePickerButton::new(&mut state.date_{})); }}); }});\n",
-                        pos.x, pos.y, size.x, size.y, escape(&w.props.text), w.id
-                    ));
-                }
-                WidgetKind::Password => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-							{origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-							ui.add_sized(egui::vec2({w:.1},{h:.1}), \
-								egui::TextEdit::singleline(&mut state.pass_{id}).password(true).hint_text(\"password\") \
-							); \
-						}});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        id = w.id,
-                    ));
-                }
-                WidgetKind::AngleSelector => {
-                    out.push_str(&format!(
-						"    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-							{origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-							ui.add_sized(egui::vec2({w:.1},{h:.1}), \
-								egui::Slider::new(&mut state.angle_{id}, {min:.3}..={max:.3}).suffix(\"°\").text(\"{label}\") \
-							); \
-						}});\n",
-						x=pos.x,y=pos.y,w=size.x,h=size.y,id=w.id,
-						min=w.props.min, max=w.props.max, label=escape(&w.props.text)
-					));
-                }
-                WidgetKind::Tree => {
-                    // Helpers live only in the generator (not emitted), so we can use any Rust we want here:
-                    #[derive(Clone)]
-                    struct Node {
-                        label: String,
-                        children: Vec<Node>,
+    /// Syntax theme for the code panel, from those suiting the current
+    /// dark or light mode
+    fn theme_picker(&mut self, ui: &mut egui::Ui) {
+        let dark = ui.visuals().dark_mode;
+        let mut chosen = None;
+        ui.horizontal(|ui| {
+            ui.label("Code theme:");
+            egui::ComboBox::from_id_salt("code_theme")
+                .selected_text(self.highlighter.theme_name())
+                .show_ui(ui, |ui| {
+                    for name in self.highlighter.themes(dark) {
+                        let current = name == self.highlighter.theme_name();
+                        if ui.selectable_label(current, name).clicked() {
+                            chosen = Some(name.to_owned());
+                        }
                     }
+                });
+        });
+        if let Some(name) = chosen {
+            self.highlighter.set_theme(&name);
+            self.prefs.set_theme(dark, name);
+        }
+    }
 
-                    fn parse_nodes(lines: &[String]) -> Vec<Node> {
-                        let items: Vec<(usize, String)> = lines
-                            .iter()
-                            .map(|s| {
-                                let indent = s.chars().take_while(|c| *c == ' ').count() / 2;
-                                (indent, s.trim().to_string())
-                            })
-                            .filter(|(_, s)| !s.is_empty())
-                            .collect();
-
-                        fn build<I: Iterator<Item = (usize, String)>>(
-                            it: &mut std::iter::Peekable<I>,
-                            level: usize,
-                        ) -> Vec<Node> {
-                            let mut out = Vec::new();
-                            while let Some((ind, _)) = it.peek().cloned() {
-                                if ind < level {
-                                    break;
-                                }
-                                if ind > level {
-                                    break;
-                                }
-                                let (_, label) = it.next().unwrap();
-                                let children = build(it, level + 1);
-                                out.push(Node { label, children });
-                            }
-                            out
-                        }
+    /// What the generated code depends on
+    fn codegen_key(&self) -> CodegenKey {
+        CodegenKey {
+            revision: self.project.revision,
+            format: self.codegen_format,
+            layout: self.codegen_layout,
+            comments: self.codegen_comments,
+            web: self.codegen_web,
+            component: self.codegen_component.clone(),
+        }
+    }
 
-                        let mut it = items.into_iter().peekable();
-                        build(&mut it, 0)
-                    }
+    /// Regenerate the code shown in the Code tab
+    fn generate_code(&mut self) {
+        let (code, map) = self
+            .codegen_format
+            .generator(self.codegen_context())
+            .generate_mapped();
+        if code != self.generated {
+            self.previous_generated = std::mem::replace(&mut self.generated, code);
+        }
+        self.source_map = map;
+        self.generated_for = Some(self.codegen_key());
+    }
 
-                    fn nodes_to_literal(nodes: &[Node]) -> String {
-                        fn one(n: &Node) -> String {
-                            let kids = if n.children.is_empty() {
-                                "vec![]".to_string()
-                            } else {
-                                format!(
-                                    "vec![{}]",
-                                    n.children.iter().map(one).collect::<Vec<_>>().join(", ")
-                                )
-                            };
-                            format!(
-                                "GenTreeNode {{ label: \"{}\".to_string(), children: {} }}",
-                                crate::widget::escape(&n.label),
-                                kids
-                            )
-                        }
-                        format!(
-                            "vec![{}]",
-                            nodes.iter().map(one).collect::<Vec<_>>().join(", ")
-                        )
-                    }
+    /// Note when an edit last bumped the project revision, for debouncing
+    fn track_edits(&mut self, ctx: &egui::Context) {
+        if self.project.revision != self.seen_revision {
+            self.seen_revision = self.project.revision;
+            self.edited_at = ctx.input(|i| i.time);
+        }
+    }
 
-                    let items = if w.props.items.is_empty() {
-                        vec!["Root".into(), "  Child".into()]
-                    } else {
-                        w.props.items.clone()
-                    };
+    /// Write unsaved changes to the recovery file, at most every
+    /// `AUTOSAVE_INTERVAL`
+    fn autosave(&mut self, ctx: &egui::Context) {
+        if self.project.revision == self.autosaved_revision {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        let due = self.autosaved_at + AUTOSAVE_INTERVAL;
+        if now < due {
+            ctx.request_repaint_after_secs((due - now) as f32);
+            return;
+        }
+        if let Err(e) = recovery::save(&self.project, self.current_file.as_deref()) {
+            self.set_status(format!("Autosave failed: {e}"));
+        }
+        self.autosaved_revision = self.project.revision;
+        self.autosaved_at = now;
+    }
 
-                    let nodes_literal = {
-                        let nodes = parse_nodes(&items);
-                        nodes_to_literal(&nodes)
-                    };
+    /// File name and an unsaved-changes marker in the window title
+    fn update_title(&mut self, ctx: &egui::Context) {
+        let name = self
+            .current_file
+            .as_deref()
+            .and_then(Path::file_name)
+            .map_or("Untitled".into(), |n| n.to_string_lossy());
+        let marker = if self.has_unsaved_changes() { " *" } else { "" };
+        let title = format!("{name}{marker} - egui RAD GUI Builder");
+        if title != self.title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+    }
 
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-							{origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-							let nodes: Vec<GenTreeNode> = {nodes}; \
-							egui::ScrollArea::vertical().auto_shrink([false,false]).show(ui, |ui| {{ \
-								gen_show_tree(ui, &nodes); \
-							}}); \
-						}});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        nodes = nodes_literal,
-                    ));
-                }
-                WidgetKind::TextArea => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            ui.add_sized(egui::vec2({w:.1},{h:.1}), \
-                                egui::TextEdit::multiline(&mut state.textarea_{id}).desired_rows(5) \
-                            ); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        id = w.id,
-                    ));
-                }
-                WidgetKind::DragValue => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            ui.horizontal(|ui| {{ \
-                                ui.label(\"{label}\"); \
-                                ui.add(egui::DragValue::new(&mut state.drag_{id}).range({min:.3}..={max:.3})); \
-                            }}); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        id = w.id,
-                        label = escape(&w.props.text),
-                        min = w.props.min,
-                        max = w.props.max,
-                    ));
-                }
-                WidgetKind::Spinner => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            ui.add(egui::Spinner::new()); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                    ));
-                }
-                WidgetKind::ColorPicker => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            ui.horizontal(|ui| {{ \
-                                ui.label(\"{label}\"); \
-                                egui::color_picker::color_edit_button_srgba(ui, &mut state.color_{id}, egui::color_picker::Alpha::OnlyBlend); \
-                            }}); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        id = w.id,
-                        label = escape(&w.props.text),
-                    ));
-                }
-                WidgetKind::Code => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            egui::ScrollArea::vertical().auto_shrink([false,false]).show(ui, |ui| {{ \
-                                ui.add(egui::TextEdit::multiline(&mut state.code_{id}).code_editor().desired_width({w:.1}).desired_rows(8)); \
-                            }}); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        id = w.id,
-                    ));
-                }
-                WidgetKind::Heading => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            ui.heading(\"{text}\"); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        text = escape(&w.props.text),
-                    ));
+    /// Offer to restore work autosaved before the builder last closed
+    fn recovery_dialog(&mut self, ctx: &egui::Context) {
+        let Some(found) = &self.recovery else {
+            return;
+        };
+        let name = found
+            .file
+            .as_deref()
+            .map_or("an unsaved project".into(), |p| p.display().to_string());
+        let minutes = found.saved_at.elapsed().map_or(0, |d| d.as_secs() / 60);
+        let mut choice = None;
+        let modal = egui::Modal::new(Id::new("recovery_dialog")).show(ctx, |ui| {
+            ui.heading("Restore unsaved work?");
+            ui.label(format!(
+                "Changes to {name} weren't saved before the builder closed. \
+                 They were autosaved {minutes} minute(s) ago."
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Restore").clicked() {
+                    choice = Some(true);
                 }
-                WidgetKind::Image => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            ui.add(egui::Image::new(\"{uri}\").fit_to_exact_size(egui::vec2({w:.1},{h:.1}))); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        uri = escape(&w.props.url),
-                    ));
+                if ui.button("Discard").clicked() {
+                    choice = Some(false);
                 }
-                WidgetKind::Placeholder => {
-                    let c = w.props.color;
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            egui::Frame::NONE.fill(egui::Color32::from_rgba_unmultiplied({r},{g},{b},{a})).corner_radius(4.0).show(ui, |ui| {{ \
-                                ui.set_min_size(egui::vec2({w:.1},{h:.1})); \
-                                ui.centered_and_justified(|ui| ui.label(\"{text}\")); \
-                            }}); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        r = c[0], g = c[1], b = c[2], a = c[3],
-                        text = escape(&w.props.text),
-                    ));
+            });
+        });
+        match choice {
+            Some(true) => {
+                if let Some(found) = self.recovery.take() {
+                    found.restored();
+                    let max_id = found.project.widgets.iter().map(|w| w.id).max();
+                    self.next_id = max_id.map_or(1, |id| id.as_u64() + 1);
+                    self.replace_project(found.project);
+                    self.current_file = found.file;
+                    self.selected.clear();
+                    self.set_status("Restored autosaved work".into());
                 }
-                WidgetKind::Group => {
-                    let title_code = if w.props.text.is_empty() {
-                        String::new()
-                    } else {
-                        format!("ui.strong(\"{}\"); ui.separator(); ", escape(&w.props.text))
-                    };
-                    let layout_fn = if w.props.horizontal { "horizontal" } else { "vertical" };
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            egui::Frame::group(ui.style()).show(ui, |ui| {{ \
-                                ui.set_min_size(egui::vec2({iw:.1},{ih:.1})); \
-                                ui.{layout_fn}(|ui| {{ {title}/* group contents */ }}); \
-                            }}); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        iw = size.x - 12.0,
-                        ih = size.y - 12.0,
-                        title = title_code,
-                        layout_fn = layout_fn,
-                    ));
+            }
+            Some(false) => {
+                if let Some(found) = self.recovery.take() {
+                    found.discard();
                 }
-                WidgetKind::ScrollBox => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            egui::ScrollArea::both().max_width({sw:.1}).max_height({sh:.1}).auto_shrink([false,false]).show(ui, |ui| {{ \
-                                ui.label(\"{text}\"); \
-                            }}); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        sw = size.x - 4.0,
-                        sh = size.y - 4.0,
-                        text = escape(&w.props.text),
-                    ));
+            }
+            // decide next time; set aside so this session's autosaves
+            // don't overwrite it
+            None if modal.should_close() => {
+                if let Some(found) = self.recovery.take() {
+                    found.set_aside();
                 }
-                WidgetKind::TabBar => {
-                    let tabs_code: String = w.props.items.iter().enumerate().map(|(i, tab)| {
-                        format!("ui.selectable_value(&mut state.tab_{id}, {i}, \"{tab}\"); ",
-                            id = w.id, i = i, tab = escape(tab))
-                    }).collect();
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            ui.horizontal(|ui| {{ {tabs} }}); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        tabs = tabs_code,
-                    ));
+            }
+            None => {}
+        }
+    }
+
+    /// Confirm New or Open when they'd discard unsaved changes
+    fn discard_dialog(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.pending_discard.clone() else {
+            return;
+        };
+        let mut choice = None;
+        let modal = egui::Modal::new(Id::new("discard_dialog")).show(ctx, |ui| {
+            ui.heading("Unsaved changes");
+            ui.label("The project has changes that haven't been saved.");
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    choice = Some(true);
                 }
-                WidgetKind::Columns => {
-                    out.push_str(&format!(
-                        "    ui.scope_builder(egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(\
-                            {origin} + egui::vec2({x:.1},{y:.1}), egui::vec2({w:.1},{h:.1}))), |ui| {{ \
-                            ui.columns({cols}, |columns| {{ \
-                                for col in columns.iter_mut() {{ col.label(\"{text}\"); }} \
-                            }}); \
-                        }});\n",
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        cols = w.props.columns.max(1),
-                        text = escape(&w.props.text),
-                    ));
+                if ui.button("Discard").clicked() {
+                    choice = Some(false);
                 }
-                WidgetKind::Window => {
-                    let title = escape(&w.props.text);
-                    out.push_str(&format!(
-                        "    egui::Window::new(\"{title}\").default_pos({origin} + egui::vec2({x:.1},{y:.1})).default_size(egui::vec2({w:.1},{h:.1})).open(&mut state.window_{id}_open).show(ctx, |ui| {{ \
-                            /* window contents */ \
-                        }});\n",
-                        title = title,
-                        x = pos.x,
-                        y = pos.y,
-                        w = size.x,
-                        h = size.y,
-                        id = w.id,
-                    ));
+                if ui.button("Cancel").clicked() {
+                    self.pending_discard = None;
                 }
-            }
+            });
+        });
+        if modal.should_close() {
+            self.pending_discard = None;
+        }
+        let Some(save) = choice else {
+            return;
         };
-
-        let mut top = Vec::new();
-        let mut bottom = Vec::new();
-        let mut left = Vec::new();
-        let mut right = Vec::new();
-        let mut center = Vec::new();
-        let mut free = Vec::new();
-        for w in &self.project.widgets {
-            match w.area {
-                Top => top.push(w),
-                Bottom => bottom.push(w),
-                Left => left.push(w),
-                Right => right.push(w),
-                Center => center.push(w),
-                Free => free.push(w),
-            }
-        }
-
-        out.push_str("fn generated_ui(ctx: &egui::Context, state: &mut GeneratedState) {\n");
-
-        // TOP
-        out.push_str("    if state.enable_top {\n");
-        out.push_str("        egui::TopBottomPanel::top(\"gen_top\")\n");
-        out.push_str("            .resizable(true)\n");
-        out.push_str("            .show(ctx, |ui| {\n");
-        for w in top {
-            emit_widget(w, &mut out, "ui.min_rect().min");
-        }
-        out.push_str("            });\n");
-        out.push_str("    }\n");
-
-        // BOTTOM
-        out.push_str("    if state.enable_bottom {\n");
-        out.push_str("        egui::TopBottomPanel::bottom(\"gen_bottom\")\n");
-        out.push_str("            .resizable(true)\n");
-        out.push_str("            .show(ctx, |ui| {\n");
-        for w in bottom {
-            emit_widget(w, &mut out, "ui.min_rect().min");
-        }
-        out.push_str("            });\n");
-        out.push_str("    }\n");
-
-        // LEFT
-        out.push_str("    if state.enable_left {\n");
-        out.push_str("        egui::SidePanel::left(\"gen_left\")\n");
-        out.push_str("            .resizable(true)\n");
-        out.push_str("            .show(ctx, |ui| {\n");
-        for w in left {
-            emit_widget(w, &mut out, "ui.min_rect().min");
-        }
-        out.push_str("            });\n");
-        out.push_str("    }\n");
-
-        // RIGHT
-        out.push_str("    if state.enable_right {\n");
-        out.push_str("        egui::SidePanel::right(\"gen_right\")\n");
-        out.push_str("            .resizable(true)\n");
-        out.push_str("            .show(ctx, |ui| {\n");
-        for w in right {
-            emit_widget(w, &mut out, "ui.min_rect().min");
-        }
-        out.push_str("            });\n");
-        out.push_str("    }\n");
-
-        // CENTER (+ FREE): use CentralPanel; widgets are placed absolutely within it.
-        out.push_str("    egui::CentralPanel::default().show(ctx, |ui| {\n");
-        // fixed logical canvas (keeps your designed size)
-        out.push_str(&format!(
-			"        let canvas = egui::Rect::from_min_size(ui.min_rect().min, egui::vec2({:.1}, {:.1}));\n",
-			self.project.canvas_size.x, self.project.canvas_size.y
-		));
-        out.push_str("        let _ = ui.allocate_painter(canvas.size(), egui::Sense::hover());\n");
-        for w in center {
-            emit_widget(w, &mut out, "canvas.min");
-        }
-        for w in free {
-            emit_widget(w, &mut out, "canvas.min");
-        }
-        out.push_str("    });\n");
-
-        out.push_str("}\n\n");
-
-        // ---------- Example eframe app (updated to call generated_ui with ctx) ----------
-        if self.codegen_comments {
-            out.push_str("// =============================================================================\n");
-            out.push_str("// Application entry point\n");
-            out.push_str("// =============================================================================\n\n");
-        }
-
-        out.push_str(
-            "pub struct GeneratedApp {\n\
-			     state: GeneratedState,\n\
-			 }\n\n\
-			 impl Default for GeneratedApp {\n\
-			     fn default() -> Self {\n\
-			         Self { state: Default::default() }\n\
-			     }\n\
-			 }\n\n\
-			 impl eframe::App for GeneratedApp {\n\
-			     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {\n\
-			         generated_ui(ctx, &mut self.state);\n\
-			     }\n\
-			 }\n\n\
-			 fn main() -> eframe::Result<()> {\n\
-			     let native_options = eframe::NativeOptions::default();\n\
-			     eframe::run_native(\n\
-			         \"Generated UI\",\n\
-			         native_options,\n\
-			         Box::new(|_cc| Ok(Box::new(GeneratedApp::default()))),\n\
-			     )\n\
-			 }\n",
-        );
-
-        out
+        self.pending_discard = None;
+        if save && !self.save() {
+            return;
+        }
+        self.run(action);
     }
 
-    /// Generate code split into separate conceptual files (shown with file headers)
-    fn generate_separate_files(&self) -> String {
-        let single = self.generate_single_file();
-
-        // For now, show the code with clear section headers
-        // A future enhancement could actually save separate files
-        let mut out = String::new();
-
-        out.push_str(
-            "// =============================================================================\n",
-        );
-        out.push_str("// FILE: Cargo.toml\n");
-        out.push_str(
-            "// =============================================================================\n",
-        );
-        out.push_str("[package]\n");
-        out.push_str("name = \"generated-ui\"\n");
-        out.push_str("version = \"0.1.0\"\n");
-        out.push_str("edition = \"2021\"\n\n");
-        out.push_str("[dependencies]\n");
-        out.push_str("eframe = \"0.33\"\n");
-        out.push_str("egui = \"0.33\"\n");
-        out.push_str("egui_extras = { version = \"0.33\", features = [\"chrono\"] }\n");
-        out.push_str("chrono = \"0.4\"\n\n");
-
-        out.push_str(
-            "// =============================================================================\n",
-        );
-        out.push_str("// FILE: src/main.rs\n");
-        out.push_str(
-            "// =============================================================================\n",
-        );
-        out.push_str(&single);
-
-        out
+    /// Run `action` now, or once the user agrees to lose unsaved changes
+    fn discard_then(&mut self, action: Discard) {
+        if self.has_unsaved_changes() {
+            self.pending_discard = Some(action);
+        } else {
+            self.run(action);
+        }
     }
 
-    /// Generate only the UI function (for embedding in existing code)
-    fn generate_ui_only(&self) -> String {
-        let mut out = String::new();
-
-        if self.codegen_comments {
-            out.push_str("// UI function generated by egui RAD GUI Builder\n");
-            out.push_str("// Embed this in your existing application\n\n");
+    fn run(&mut self, action: Discard) {
+        match action {
+            Discard::New => self.new_project(),
+            Discard::Open => self.open_project(),
+            Discard::OpenRecent(path) => self.load_project(path),
         }
+    }
 
-        // We need to include the state struct since UI references it
-        out.push_str("// Required state struct for the UI\n");
-
-        // Generate just the state struct and the UI function
-        // We'll extract parts from generate_single_file
-        let has_tree = self
-            .project
-            .widgets
-            .iter()
-            .any(|w| matches!(w.kind, WidgetKind::Tree));
-        if has_tree {
-            out.push_str(
-                "#[derive(Clone)]\n\
-                 struct GenTreeNode { label: String, children: Vec<GenTreeNode> }\n\
-                 \n\
-                 fn gen_show_tree(ui: &mut egui::Ui, nodes: &[GenTreeNode]) {\n\
-                     for n in nodes {\n\
-                         if n.children.is_empty() { ui.label(&n.label); }\n\
-                         else { ui.collapsing(&n.label, |ui| gen_show_tree(ui, &n.children)); }\n\
-                     }\n\
-                 }\n\n",
+    /// File > Recent Projects
+    fn recent_menu(&mut self, ui: &mut egui::Ui) {
+        if self.prefs.recent_files.is_empty() {
+            ui.weak("No recent projects");
+        }
+        let mut open = None;
+        for path in &self.prefs.recent_files {
+            let name = path.file_name().map_or_else(
+                || path.display().to_string(),
+                |n| n.to_string_lossy().into(),
             );
+            if ui
+                .button(name)
+                .on_hover_text(path.display().to_string())
+                .clicked()
+            {
+                open = Some(path.clone());
+            }
+        }
+        if let Some(path) = open {
+            self.discard_then(Discard::OpenRecent(path));
+            ui.close_kind(egui::UiKind::Menu);
+        }
+        ui.separator();
+        ui.checkbox(&mut self.prefs.reopen_last, "Reopen on Startup")
+            .on_hover_text("Open the most recent project when the builder starts");
+        if ui
+            .add_enabled(
+                !self.prefs.recent_files.is_empty(),
+                egui::Button::new("Clear Recent"),
+            )
+            .clicked()
+        {
+            self.prefs.recent_files.clear();
+            ui.close_kind(egui::UiKind::Menu);
         }
+    }
 
-        out.push_str("struct GeneratedState {\n");
-        out.push_str(
-            "    enable_top: bool, enable_bottom: bool, enable_left: bool, enable_right: bool,\n",
-        );
-        for w in &self.project.widgets {
-            match w.kind {
-                WidgetKind::TextEdit => out.push_str(&format!("    text_{}: String,\n", w.id)),
-                WidgetKind::Checkbox => out.push_str(&format!("    checked_{}: bool,\n", w.id)),
-                WidgetKind::Slider => out.push_str(&format!("    value_{}: f32,\n", w.id)),
-                WidgetKind::ProgressBar => out.push_str(&format!("    progress_{}: f32,\n", w.id)),
-                WidgetKind::SelectableLabel => out.push_str(&format!("    sel_{}: bool,\n", w.id)),
-                WidgetKind::RadioGroup | WidgetKind::ComboBox | WidgetKind::MenuButton => {
-                    out.push_str(&format!("    sel_{}: usize,\n", w.id))
-                }
-                WidgetKind::CollapsingHeader => {
-                    out.push_str(&format!("    open_{}: bool,\n", w.id))
-                }
-                WidgetKind::DatePicker => {
-                    out.push_str(&format!("    date_{}: chrono::NaiveDate,\n", w.id))
-                }
-                WidgetKind::Password => out.push_str(&format!("    pass_{}: String,\n", w.id)),
-                WidgetKind::AngleSelector => out.push_str(&format!("    angle_{}: f32,\n", w.id)),
-                WidgetKind::TextArea => out.push_str(&format!("    textarea_{}: String,\n", w.id)),
-                WidgetKind::DragValue => out.push_str(&format!("    drag_{}: f32,\n", w.id)),
-                WidgetKind::ColorPicker => {
-                    out.push_str(&format!("    color_{}: egui::Color32,\n", w.id))
-                }
-                WidgetKind::Code => out.push_str(&format!("    code_{}: String,\n", w.id)),
-                _ => {}
-            }
+    /// With auto-generate on, regenerate once edits have paused for
+    /// `AUTO_GENERATE_DELAY`
+    fn auto_regenerate(&mut self, ctx: &egui::Context) {
+        if !self.auto_generate
+            || self.project.widgets.is_empty()
+            || self.generated_for.as_ref() == Some(&self.codegen_key())
+        {
+            return;
+        }
+        let idle = ctx.input(|i| i.time) - self.edited_at;
+        if idle >= AUTO_GENERATE_DELAY {
+            self.generate_code();
+        } else {
+            ctx.request_repaint_after_secs((AUTO_GENERATE_DELAY - idle) as f32);
         }
-        out.push_str("}\n\n");
+    }
+}
 
-        out.push_str("// Call this function from your eframe::App::update method:\n");
-        out.push_str("// generated_ui(ctx, &mut self.state);\n\n");
+/// Seconds without edits before auto-generate regenerates the code
+const AUTO_GENERATE_DELAY: f64 = 0.3;
+
+/// Seconds between writes of unsaved changes to the recovery file
+const AUTOSAVE_INTERVAL: f64 = 10.0;
+
+/// A project that didn't read, for the Load Problems window
+struct LoadDiagnostics {
+    /// The file it came from, or `None` for Import JSON
+    file: Option<PathBuf>,
+    json: String,
+    /// Why it didn't read; `None` once read leniently
+    error: Option<LoadError>,
+    /// What the lenient read left out
+    problems: Vec<String>,
+}
 
-        // Extract just the generated_ui function from single file output
-        let single = self.generate_single_file();
-        if let Some(start) = single.find("fn generated_ui(") {
-            // Find the end of the function (look for the closing brace followed by app struct)
-            if let Some(end) = single[start..].find("\npub struct GeneratedApp") {
-                out.push_str(&single[start..start + end]);
-            } else {
-                // Fallback: include from generated_ui to end
-                out.push_str(&single[start..]);
-            }
-        }
+/// Action that replaces the project, waiting on confirmation
+#[derive(Clone)]
+enum Discard {
+    New,
+    Open,
+    OpenRecent(PathBuf),
+}
+
+/// Earlier code the diff view compares the output with
+enum DiffBase {
+    /// `RadBuilderApp::previous_generated`
+    Previous,
+    /// A file chosen on disk, e.g. the copy in a repository
+    File(PathBuf, String),
+}
+
+/// State of the Code tab's find bar
+#[derive(Default)]
+struct CodeFind {
+    open: bool,
+    query: String,
+    match_case: bool,
+    /// Index of the highlighted match among all matches
+    current: usize,
+    /// Select and scroll to the current match next frame
+    jump: bool,
+    /// Focus the search box next frame
+    focus: bool,
+}
 
-        out
+impl CodeFind {
+    /// Moves `current` by `step` matches, wrapping around
+    fn step(&mut self, step: isize, matches: usize) {
+        if matches > 0 {
+            self.current = (self.current as isize + step).rem_euclid(matches as isize) as usize;
+            self.jump = true;
+        }
     }
 }
 
+/// Canvas size and anchored widget rects before a canvas resize. Each step
+/// of the resize is worked out from these rather than from the previous
+/// step, so shrinking and growing back restores the design even where
+/// `Anchor::apply` clamped a stretched size.
+struct ResizeBase {
+    /// `Project::revision` after the latest step; any other edit starts a
+    /// new resize from the design as it is then
+    revision: u64,
+    canvas: egui::Vec2,
+    rects: Vec<(WidgetId, Pos2, egui::Vec2)>,
+}
+
+/// Everything the generated code depends on; it's current while this
+/// matches what it was generated from
+#[derive(Clone, PartialEq)]
+struct CodegenKey {
+    revision: u64,
+    format: CodeGenFormat,
+    layout: CodeGenLayout,
+    comments: bool,
+    web: bool,
+    component: ComponentOptions,
+}
+
 impl eframe::App for RadBuilderApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let prefs = &mut self.prefs;
+        prefs.palette_open = self.palette_open;
+        prefs.grid_size = self.grid_size;
+        prefs.show_grid = self.show_grid;
+        prefs.syntax_highlighting = self.syntax_highlighting;
+        prefs.auto_generate = self.auto_generate;
+        prefs.codegen_format = self.codegen_format;
+        prefs.codegen_comments = self.codegen_comments;
+        prefs.save(storage);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Keys that also edit text don't act on widgets while typing, e.g.
+        // in the find box
+        let typing = ctx.wants_keyboard_input();
         // Keyboard shortcuts - check input first, then apply changes
         let (
             delete_pressed,
@@ -2830,27 +3849,57 @@ impl eframe::App for RadBuilderApp {
             bring_front,
             send_back,
             toggle_preview,
+            find_code,
+            find_project,
         ) = ctx.input(|i| {
-            let del = i.key_pressed(egui::Key::Delete) || i.key_pressed(egui::Key::Backspace);
-            let dup = i.modifiers.command && i.key_pressed(egui::Key::D);
+            let del = !typing
+                && (i.key_pressed(egui::Key::Delete) || i.key_pressed(egui::Key::Backspace));
+            let dup = !typing && i.modifiers.command && i.key_pressed(egui::Key::D);
             let gencode = i.modifiers.command && i.key_pressed(egui::Key::G);
-            let copy = i.modifiers.command && i.key_pressed(egui::Key::C);
-            let paste = i.modifiers.command && i.key_pressed(egui::Key::V);
+            let copy = !typing && i.modifiers.command && i.key_pressed(egui::Key::C);
+            let paste = !typing && i.modifiers.command && i.key_pressed(egui::Key::V);
             // Arrow keys for nudging
-            let up = i.key_pressed(egui::Key::ArrowUp);
-            let down = i.key_pressed(egui::Key::ArrowDown);
-            let left = i.key_pressed(egui::Key::ArrowLeft);
-            let right = i.key_pressed(egui::Key::ArrowRight);
+            let up = !typing && i.key_pressed(egui::Key::ArrowUp);
+            let down = !typing && i.key_pressed(egui::Key::ArrowDown);
+            let left = !typing && i.key_pressed(egui::Key::ArrowLeft);
+            let right = !typing && i.key_pressed(egui::Key::ArrowRight);
             // Z-order: ] = bring to front, [ = send to back
-            let front = i.key_pressed(egui::Key::CloseBracket);
-            let back = i.key_pressed(egui::Key::OpenBracket);
+            let front = !typing && i.key_pressed(egui::Key::CloseBracket);
+            let back = !typing && i.key_pressed(egui::Key::OpenBracket);
             // F5: Toggle preview mode
             let preview = i.key_pressed(egui::Key::F5);
+            // Ctrl+F: find in code, Ctrl+Shift+F: find in project
+            let find = i.modifiers.command && i.key_pressed(egui::Key::F);
+            let find_code = find && !i.modifiers.shift;
+            let find_project = find && i.modifiers.shift;
             (
-                del, dup, gencode, copy, paste, up, down, left, right, front, back, preview,
+                del,
+                dup,
+                gencode,
+                copy,
+                paste,
+                up,
+                down,
+                left,
+                right,
+                front,
+                back,
+                preview,
+                find_code,
+                find_project,
             )
         });
 
+        if find_code {
+            self.right_panel_tab = 1;
+            self.code_find.open = true;
+            self.code_find.focus = true;
+        }
+        if find_project {
+            self.right_panel_tab = 3;
+            self.find_focus = true;
+        }
+
         // F5: Toggle preview mode
         if toggle_preview {
             self.preview_mode = !self.preview_mode;
@@ -2860,6 +3909,7 @@ impl eframe::App for RadBuilderApp {
         if delete_pressed && !self.selected.is_empty() {
             let to_delete: Vec<_> = self.selected.clone();
             self.project.widgets.retain(|w| !to_delete.contains(&w.id));
+            self.project.touch();
             self.selected.clear();
         }
 
@@ -2886,6 +3936,7 @@ impl eframe::App for RadBuilderApp {
                     w.pos.y = w.pos.y.max(0.0);
                 }
             }
+            self.project.touch();
         }
 
         // Z-order controls (apply to all selected)
@@ -2897,6 +3948,7 @@ impl eframe::App for RadBuilderApp {
                     w.z = max_z + 1 + i as i32;
                 }
             }
+            self.project.touch();
         }
         if send_back && !self.selected.is_empty() {
             let min_z = self.project.widgets.iter().map(|w| w.z).min().unwrap_or(0);
@@ -2906,6 +3958,7 @@ impl eframe::App for RadBuilderApp {
                     w.z = min_z - 1 - i as i32;
                 }
             }
+            self.project.touch();
         }
 
         // Ctrl+C: Copy first selected widget
@@ -2925,7 +3978,9 @@ impl eframe::App for RadBuilderApp {
             pasted.z = new_id.as_z();
             pasted.pos.x += 20.0;
             pasted.pos.y += 20.0;
+            pasted.slot = None;
             self.project.widgets.push(pasted);
+            self.project.touch();
             self.selected = vec![new_id];
         }
 
@@ -2948,16 +4003,20 @@ impl eframe::App for RadBuilderApp {
                     dup.z = new_id.as_z();
                     dup.pos.x += 20.0;
                     dup.pos.y += 20.0;
+                    dup.slot = None;
                     self.project.widgets.push(dup);
                     new_ids.push(new_id);
                 }
             }
+            if !new_ids.is_empty() {
+                self.project.touch();
+            }
             self.selected = new_ids;
         }
 
         // Ctrl+G: Generate code
         if generate_pressed {
-            self.generated = self.generate_code();
+            self.generate_code();
         }
 
         egui::TopBottomPanel::top("menubar").show(ctx, |ui| self.top_bar(ui));
@@ -2985,25 +4044,62 @@ impl eframe::App for RadBuilderApp {
                     {
                         self.right_panel_tab = 1;
                     }
+                    if ui
+                        .selectable_label(self.right_panel_tab == 2, "Assets")
+                        .clicked()
+                    {
+                        self.right_panel_tab = 2;
+                    }
+                    if ui
+                        .selectable_label(self.right_panel_tab == 3, "Find")
+                        .on_hover_text("Find in project (Ctrl+Shift+F)")
+                        .clicked()
+                    {
+                        self.right_panel_tab = 3;
+                    }
+                    if ui
+                        .selectable_label(self.right_panel_tab == 4, "Problems")
+                        .on_hover_text("Check the design for mistakes")
+                        .clicked()
+                    {
+                        self.right_panel_tab = 4;
+                    }
                 });
                 ui.separator();
 
                 match self.right_panel_tab {
                     0 => self.inspector_ui(ui),
                     1 => self.generated_panel(ui),
+                    2 => self.assets_ui(ui),
+                    3 => self.find_ui(ui),
+                    4 => self.problems_ui(ui),
                     _ => {}
                 }
             });
 
+        self.sync_assets(ctx);
+
+        // follow the builder's dark/light mode with the theme chosen for it
+        let dark = ctx.style().visuals.dark_mode;
+        let theme = self
+            .prefs
+            .theme(dark)
+            .unwrap_or(Highlighter::default_theme(dark));
+        self.highlighter.set_theme(theme);
+
         // Set edit mode for widget rendering (inverse of preview mode)
         ctx.data_mut(|d| d.insert_temp(Id::new("edit_mode"), !self.preview_mode));
 
         self.preview_panels_ui(ctx);
 
+        self.track_edits(ctx);
+        self.autosave(ctx);
+        self.update_title(ctx);
+        self.recovery_dialog(ctx);
+        self.diagnostics_window(ctx);
+        self.discard_dialog(ctx);
         // Auto-generate code if enabled and widgets exist
-        if self.auto_generate && !self.project.widgets.is_empty() {
-            self.generated = self.generate_code();
-        }
+        self.auto_regenerate(ctx);
 
         if self.spawning.is_some() {
             ctx.set_cursor_icon(egui::CursorIcon::Grabbing);
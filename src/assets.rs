@@ -0,0 +1,335 @@
+//! Project assets (images and fonts) referenced by designs.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Folder (relative to an exported project) that assets are copied into.
+pub(crate) const EXPORT_DIR: &str = "assets";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub(crate) struct AssetId(u64);
+
+impl AssetId {
+    pub(crate) const fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    pub(crate) const fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for AssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) enum AssetKind {
+    Image,
+    Font,
+}
+
+impl AssetKind {
+    /// Guess the asset kind from a file extension.
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "png" | "jpg" | "jpeg" => Some(AssetKind::Image),
+            "ttf" | "otf" => Some(AssetKind::Font),
+            _ => None,
+        }
+    }
+
+    pub const fn display_name(&self) -> &'static str {
+        match self {
+            AssetKind::Image => "Image",
+            AssetKind::Font => "Font",
+        }
+    }
+
+    pub const fn icon(&self) -> &'static str {
+        match self {
+            AssetKind::Image => "🖼",
+            AssetKind::Font => "🗛",
+        }
+    }
+
+    /// File extensions accepted by the import dialog. Images are limited to
+    /// the formats the `image` features here and in generated projects decode.
+    pub const fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            AssetKind::Image => &["png", "jpg", "jpeg"],
+            AssetKind::Font => &["ttf", "otf"],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Asset {
+    pub(crate) id: AssetId,
+    pub(crate) kind: AssetKind,
+    /// Display name; also used as the font family name for fonts, so it is
+    /// kept non-empty and unique (see `Project::asset_name_problem`).
+    pub(crate) name: String,
+    /// Path relative to the project file (or absolute for unsaved projects).
+    pub(crate) path: String,
+    /// File contents, when the asset is embedded in the project file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) data: Option<Vec<u8>>,
+}
+
+impl Asset {
+    /// File name component of `path`.
+    pub(crate) fn file_name(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| format!("asset_{}", self.id))
+    }
+
+    /// Path of the asset inside an exported project, relative to its root.
+    /// Prefixed with the id, so assets with the same file name from different
+    /// directories don't overwrite each other.
+    pub(crate) fn export_path(&self) -> String {
+        format!("{}/{}_{}", EXPORT_DIR, self.id, self.file_name())
+    }
+
+    /// Key of a font in `egui::FontDefinitions::font_data`. It comes from the
+    /// id rather than the name, so a font can't replace another project font
+    /// or one of egui's own.
+    pub(crate) fn font_key(&self) -> String {
+        format!("asset_{}", self.id)
+    }
+
+    /// Resolve `path` against the directory holding the project file.
+    pub(crate) fn resolve(&self, base: Option<&Path>) -> PathBuf {
+        let path = Path::new(&self.path);
+        match base {
+            Some(base) if path.is_relative() => base.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+
+    /// Embedded assets always exist; others must be found on disk.
+    pub(crate) fn exists(&self, base: Option<&Path>) -> bool {
+        self.data.is_some() || self.resolve(base).is_file()
+    }
+
+    pub(crate) fn load_bytes(&self, base: Option<&Path>) -> std::io::Result<Vec<u8>> {
+        match &self.data {
+            Some(data) => Ok(data.clone()),
+            None => std::fs::read(self.resolve(base)),
+        }
+    }
+}
+
+/// Express `path` relative to `base` when it lives underneath it.
+pub(crate) fn relative_path(path: &Path, base: Option<&Path>) -> String {
+    base.and_then(|b| path.strip_prefix(b).ok())
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Re-express the paths of assets on disk for a project file moving from
+/// directory `from` to `to`, as Save As or the first save does, so they
+/// still resolve after the project is reopened from `to`.
+pub(crate) fn rebase_assets(assets: &mut [Asset], from: Option<&Path>, to: Option<&Path>) {
+    for asset in assets.iter_mut().filter(|a| a.data.is_none()) {
+        let path = asset.resolve(from);
+        // a relative path in an unsaved project has nothing to resolve against
+        if path.is_absolute() {
+            asset.path = relative_path(&path, to);
+        }
+    }
+}
+
+/// Register a font the way the generated `setup_fonts` does: its data under
+/// `Asset::font_key`, and a family named after the asset that falls back to
+/// egui's proportional fonts for missing glyphs.
+pub(crate) fn install_font(fonts: &mut egui::FontDefinitions, font: &Asset, data: egui::FontData) {
+    let key = font.font_key();
+    let fallback = fonts
+        .families
+        .get(&egui::FontFamily::Proportional)
+        .cloned()
+        .unwrap_or_default();
+    fonts.font_data.insert(key.clone(), data.into());
+    let mut family = vec![key];
+    family.extend(fallback);
+    fonts
+        .families
+        .insert(egui::FontFamily::Name(font.name.as_str().into()), family);
+}
+
+/// Copy (or write out embedded) assets into `<out_dir>/assets`.
+/// Returns the number of files written.
+pub(crate) fn export_assets(
+    assets: &[Asset],
+    base: Option<&Path>,
+    out_dir: &Path,
+) -> std::io::Result<usize> {
+    if assets.is_empty() {
+        return Ok(0);
+    }
+    std::fs::create_dir_all(out_dir.join(EXPORT_DIR))?;
+    for asset in assets {
+        let bytes = asset
+            .load_bytes(base)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("asset '{}': {}", asset.name, e)))?;
+        std::fs::write(out_dir.join(asset.export_path()), bytes)?;
+    }
+    Ok(assets.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(path: &str) -> Asset {
+        Asset {
+            id: AssetId::new(1),
+            kind: AssetKind::Image,
+            name: "logo".into(),
+            path: path.into(),
+            data: None,
+        }
+    }
+
+    #[test]
+    fn test_asset_kind_from_path() {
+        assert_eq!(
+            AssetKind::from_path(Path::new("a/logo.PNG")),
+            Some(AssetKind::Image)
+        );
+        assert_eq!(
+            AssetKind::from_path(Path::new("Inter.ttf")),
+            Some(AssetKind::Font)
+        );
+        assert_eq!(AssetKind::from_path(Path::new("notes.txt")), None);
+        // no decoder for these in the builder or generated projects
+        assert_eq!(AssetKind::from_path(Path::new("anim.gif")), None);
+        assert_eq!(AssetKind::from_path(Path::new("icon.svg")), None);
+        assert_eq!(AssetKind::from_path(Path::new("no_extension")), None);
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let base = Path::new("/home/me/design");
+        assert_eq!(
+            relative_path(Path::new("/home/me/design/img/logo.png"), Some(base)),
+            "img/logo.png"
+        );
+        assert_eq!(
+            relative_path(Path::new("/elsewhere/logo.png"), Some(base)),
+            "/elsewhere/logo.png"
+        );
+        assert_eq!(
+            relative_path(Path::new("/elsewhere/logo.png"), None),
+            "/elsewhere/logo.png"
+        );
+    }
+
+    #[test]
+    fn test_rebase_assets_on_save_elsewhere() {
+        let root = std::env::temp_dir().join(format!("rad_rebase_{}", std::process::id()));
+        let (old_dir, new_dir) = (root.join("old"), root.join("new"));
+        std::fs::create_dir_all(old_dir.join("img")).unwrap();
+        std::fs::create_dir_all(new_dir.join("img")).unwrap();
+        std::fs::write(old_dir.join("img/logo.png"), [1]).unwrap();
+        std::fs::write(new_dir.join("img/icon.png"), [2]).unwrap();
+
+        let mut project = crate::project::Project::default();
+        let mut inside_new = asset(&new_dir.join("img/icon.png").to_string_lossy());
+        inside_new.id = AssetId::new(2);
+        let mut embedded = asset("img/missing.png");
+        embedded.id = AssetId::new(3);
+        embedded.data = Some(vec![3]);
+        project.assets = vec![asset("img/logo.png"), inside_new, embedded];
+
+        rebase_assets(&mut project.assets, Some(&old_dir), Some(&new_dir));
+        let file = new_dir.join("project.json");
+        std::fs::write(&file, serde_json::to_string(&project).unwrap()).unwrap();
+        let reloaded =
+            crate::project::Project::from_json(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        let paths: Vec<&str> = reloaded.assets.iter().map(|a| a.path.as_str()).collect();
+        let logo = old_dir.join("img/logo.png");
+        assert_eq!(
+            paths,
+            vec![logo.to_str().unwrap(), "img/icon.png", "img/missing.png"]
+        );
+        assert!(reloaded.assets.iter().all(|a| a.exists(Some(&new_dir))));
+
+        // an unsaved project keeps absolute paths until its first save
+        let mut first = vec![asset(logo.to_str().unwrap())];
+        rebase_assets(&mut first, None, Some(&old_dir));
+        assert_eq!(first[0].path, "img/logo.png");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_asset_paths() {
+        let a = asset("img/logo.png");
+        assert_eq!(a.file_name(), "logo.png");
+        assert_eq!(a.export_path(), "assets/1_logo.png");
+        assert_eq!(
+            a.resolve(Some(Path::new("/proj"))),
+            PathBuf::from("/proj/img/logo.png")
+        );
+        assert_eq!(a.resolve(None), PathBuf::from("img/logo.png"));
+    }
+
+    #[test]
+    fn test_embedded_asset_exists() {
+        let mut a = asset("definitely/missing.png");
+        assert!(!a.exists(None));
+        a.data = Some(vec![1, 2, 3]);
+        assert!(a.exists(None));
+        assert_eq!(a.load_bytes(None).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_export_assets() {
+        let dir = std::env::temp_dir().join(format!("rad_assets_{}", std::process::id()));
+        let mut a = asset("missing/logo.png");
+        a.data = Some(vec![7; 4]);
+        // same file name, from another directory
+        let mut b = asset("other/logo.png");
+        b.id = AssetId::new(2);
+        b.data = Some(vec![9; 2]);
+        assert_eq!(export_assets(&[a, b], None, &dir).unwrap(), 2);
+        assert_eq!(
+            std::fs::read(dir.join("assets/1_logo.png")).unwrap(),
+            vec![7; 4]
+        );
+        assert_eq!(
+            std::fs::read(dir.join("assets/2_logo.png")).unwrap(),
+            vec![9; 2]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_font() {
+        let mut font = asset("fonts/Hack.ttf");
+        font.kind = AssetKind::Font;
+        // the same name as one of egui's own fonts
+        font.name = "Hack".into();
+        let mut fonts = egui::FontDefinitions::default();
+        let proportional = fonts.families[&egui::FontFamily::Proportional].clone();
+        install_font(&mut fonts, &font, egui::FontData::from_static(&[]));
+        assert!(fonts.font_data["asset_1"].font.is_empty());
+        assert!(!fonts.font_data["Hack"].font.is_empty());
+        assert_eq!(
+            fonts.families[&egui::FontFamily::Proportional],
+            proportional
+        );
+        let family = &fonts.families[&egui::FontFamily::Name("Hack".into())];
+        assert_eq!(family[0], "asset_1");
+        assert_eq!(family[1..], proportional[..]);
+    }
+}
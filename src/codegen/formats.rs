@@ -0,0 +1,520 @@
+//! The output formats offered in Settings > Code Generation.
+
+use super::{
+    CodeGenerator, GenContext, PanelHost, WEB_CANVAS_ID, banner, cargo_toml, index_html,
+    panels_code, pretty, ui_fn, uses_images, widgets,
+};
+use crate::widget::{Widget, WidgetKind};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+
+/// A complete eframe program: state, `generated_ui`, an app struct and `main`
+pub(crate) struct SingleFile<'a> {
+    pub(crate) cx: GenContext<'a>,
+}
+
+impl CodeGenerator for SingleFile<'_> {
+    fn context(&self) -> &GenContext<'_> {
+        &self.cx
+    }
+
+    fn emit_app_shell(&self) -> TokenStream {
+        let project = self.cx.project;
+        let has_fonts = project.fonts().next().is_some();
+        let font_setup = if has_fonts {
+            font_setup(&self.cx)
+        } else {
+            quote!()
+        };
+        let mut setup = Vec::new();
+        if uses_images(project) {
+            setup.push(quote!(egui_extras::install_image_loaders(&cc.egui_ctx);));
+        }
+        if has_fonts {
+            setup.push(quote!(setup_fonts(&cc.egui_ctx);));
+        }
+        let create = if setup.is_empty() {
+            quote!(Box::new(|_cc| Ok(Box::new(GeneratedApp::default()))))
+        } else {
+            quote! {
+                Box::new(|cc| {
+                    #(#setup)*
+                    Ok(Box::new(GeneratedApp::default()))
+                })
+            }
+        };
+        let native_main = quote! {
+            fn main() -> eframe::Result<()> {
+                let native_options = eframe::NativeOptions::default();
+                eframe::run_native("Generated UI", native_options, #create)
+            }
+        };
+        let (native_main, web_main) = if self.cx.web {
+            (
+                quote!(#[cfg(not(target_arch = "wasm32"))] #native_main),
+                web_main(&create),
+            )
+        } else {
+            (native_main, quote!())
+        };
+        quote! {
+            pub struct GeneratedApp {
+                state: GeneratedState,
+            }
+
+            impl Default for GeneratedApp {
+                fn default() -> Self {
+                    Self { state: Default::default() }
+                }
+            }
+
+            impl eframe::App for GeneratedApp {
+                fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+                    generated_ui(ctx, &mut self.state);
+                }
+            }
+
+            #font_setup
+
+            #native_main
+            #web_main
+        }
+    }
+
+    fn emit_output(&self) -> String {
+        let mut out = String::new();
+
+        // Header comment
+        if self.cx.comments {
+            out.push_str(&banner(&[
+                "Generated by egui RAD GUI Builder",
+                "https://github.com/timschmidt/egui-rad-builder",
+            ]));
+            out.push('\n');
+        } else {
+            out.push_str("// --- generated by egui RAD GUI Builder ---\n");
+        }
+
+        let state = self.emit_state();
+        let ui_fn = ui_fn(self);
+        out.push_str(&pretty(quote! {
+            use eframe::egui;
+            #state
+            #ui_fn
+        }));
+        out.push('\n');
+
+        if self.cx.comments {
+            out.push_str(&banner(&["Application entry point"]));
+            out.push('\n');
+        }
+        out.push_str(&pretty(self.emit_app_shell()));
+
+        out
+    }
+}
+
+/// The single-file program preceded by the Cargo.toml it builds with
+pub(crate) struct SeparateFiles<'a> {
+    pub(crate) main: SingleFile<'a>,
+}
+
+impl CodeGenerator for SeparateFiles<'_> {
+    fn context(&self) -> &GenContext<'_> {
+        &self.main.cx
+    }
+
+    fn emit_app_shell(&self) -> TokenStream {
+        self.main.emit_app_shell()
+    }
+
+    fn emit_output(&self) -> String {
+        // For now, show the code with clear section headers
+        // A future enhancement could actually save separate files
+        let mut out = String::new();
+        out.push_str(&banner(&["FILE: Cargo.toml"]));
+        out.push_str(&cargo_toml(
+            self.main.cx.project,
+            "generated-ui",
+            self.main.cx.web,
+        ));
+        out.push('\n');
+        if self.main.cx.web {
+            out.push_str(&banner(&["FILE: index.html"]));
+            out.push_str(&index_html("generated-ui"));
+            out.push('\n');
+        }
+        out.push_str(&banner(&["FILE: src/main.rs"]));
+        out.push_str(&self.main.emit_output());
+        out
+    }
+}
+
+/// Just the state struct and `generated_ui`, for embedding in an existing app
+pub(crate) struct UiOnly<'a> {
+    pub(crate) cx: GenContext<'a>,
+}
+
+impl CodeGenerator for UiOnly<'_> {
+    fn context(&self) -> &GenContext<'_> {
+        &self.cx
+    }
+
+    /// The host app constructs the state, so no `Default` impl
+    fn emit_state(&self) -> TokenStream {
+        let helpers = widgets::helpers(self.cx.project);
+        let state =
+            widgets::state_struct(self.cx.project, &format_ident!("GeneratedState"), &quote!());
+        quote!(#helpers #state)
+    }
+
+    fn emit_app_shell(&self) -> TokenStream {
+        quote!()
+    }
+
+    fn emit_output(&self) -> String {
+        let mut out = String::new();
+
+        if self.cx.comments {
+            out.push_str("// UI function generated by egui RAD GUI Builder\n");
+            out.push_str("// Embed this in your existing application\n\n");
+        }
+
+        // We need to include the state struct since UI references it
+        out.push_str("// Required state struct for the UI\n");
+        out.push_str(&pretty(self.emit_state()));
+        out.push('\n');
+
+        out.push_str("// Call this function from your eframe::App::update method:\n");
+        out.push_str("// generated_ui(ctx, &mut self.state);\n");
+        out.push_str(&pretty(ui_fn(self)));
+
+        out
+    }
+}
+
+/// A struct owning its state, drawn into whatever `ui` the host hands it
+pub(crate) struct Component<'a> {
+    pub(crate) cx: GenContext<'a>,
+}
+
+impl Component<'_> {
+    fn name(&self) -> Ident {
+        self.cx
+            .component
+            .ident()
+            .unwrap_or_else(|| format_ident!("MyPanel"))
+    }
+
+    fn response_name(&self) -> Ident {
+        format_ident!("{}Response", self.name())
+    }
+
+    /// `clicked_N` flags for every button and link, reported back in the response
+    fn clicks(&self) -> Vec<Ident> {
+        self.cx
+            .project
+            .widgets
+            .iter()
+            .filter(|w| is_clickable(w))
+            .map(|w| format_ident!("clicked_{}", w.id.as_u64()))
+            .collect()
+    }
+}
+
+fn is_clickable(w: &Widget) -> bool {
+    matches!(
+        w.kind,
+        WidgetKind::Button | WidgetKind::ImageTextButton | WidgetKind::Link
+    )
+}
+
+impl CodeGenerator for Component<'_> {
+    fn context(&self) -> &GenContext<'_> {
+        &self.cx
+    }
+
+    /// The component struct with its `Default`, and the response it returns
+    fn emit_state(&self) -> TokenStream {
+        let project = self.cx.project;
+        let vis = self.cx.component.visibility.tokens();
+        let (name, response) = (self.name(), self.response_name());
+        let helpers = widgets::helpers(project);
+        let state = widgets::state_struct(project, &name, &vis);
+        let state_default = widgets::state_default(project, &name);
+        let clicks = self.clicks();
+        let doc = format!(" What the user did in `{name}` this frame");
+        quote! {
+            #helpers
+            #state
+            #state_default
+
+            #[doc = #doc]
+            #vis struct #response {
+                /// The area the component covers
+                #vis response: egui::Response,
+                #(#vis #clicks: bool,)*
+            }
+        }
+    }
+
+    fn emit_click(&self, w: &Widget) -> TokenStream {
+        if !is_clickable(w) {
+            return quote!();
+        }
+        let clicked = format_ident!("clicked_{}", w.id.as_u64());
+        quote!(#clicked = true;)
+    }
+
+    /// `ui()` on the component, and `egui::Widget` so it works with `ui.add`
+    fn emit_app_shell(&self) -> TokenStream {
+        let vis = self.cx.component.visibility.tokens();
+        let (name, response) = (self.name(), self.response_name());
+        let clicks = self.clicks();
+        // windows float over the whole context rather than the host ui
+        let ctx = if self
+            .cx
+            .project
+            .widgets
+            .iter()
+            .any(|w| w.kind == WidgetKind::Window)
+        {
+            quote!(let ctx = &ui.ctx().clone();)
+        } else {
+            quote!()
+        };
+        let panels = panels_code(self, PanelHost::Ui);
+        quote! {
+            impl #name {
+                /// Draw the component into `ui`
+                #vis fn ui(&mut self, ui: &mut egui::Ui) -> #response {
+                    let state = self;
+                    #ctx
+                    #(let mut #clicks = false;)*
+                    let response = ui.scope(|ui| { #panels }).response;
+                    #response { response, #(#clicks,)* }
+                }
+            }
+
+            impl egui::Widget for &mut #name {
+                fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+                    #name::ui(self, ui).response
+                }
+            }
+        }
+    }
+
+    fn emit_output(&self) -> String {
+        let mut out = String::new();
+
+        if self.cx.comments {
+            out.push_str(&banner(&[
+                "Generated by egui RAD GUI Builder",
+                "https://github.com/timschmidt/egui-rad-builder",
+            ]));
+            out.push('\n');
+            let name = self.name();
+            out.push_str(&format!(
+                "// Keep a `{name}` in your app and show it with `ui.add(&mut self.panel)`,\n"
+            ));
+            out.push_str(
+                "// or call `self.panel.ui(ui)` to find out which buttons were clicked.\n",
+            );
+        } else {
+            out.push_str("// --- generated by egui RAD GUI Builder ---\n");
+        }
+
+        let state = self.emit_state();
+        let shell = self.emit_app_shell();
+        out.push_str(&pretty(quote! {
+            use eframe::egui;
+            #state
+            #shell
+        }));
+
+        out
+    }
+}
+
+/// A Bevy plugin drawing the UI through `bevy_egui`
+pub(crate) struct BevyPlugin<'a> {
+    pub(crate) cx: GenContext<'a>,
+}
+
+impl CodeGenerator for BevyPlugin<'_> {
+    fn context(&self) -> &GenContext<'_> {
+        &self.cx
+    }
+
+    /// `GeneratedState` as a Bevy `Resource`
+    fn emit_state(&self) -> TokenStream {
+        let project = self.cx.project;
+        let name = format_ident!("GeneratedState");
+        let helpers = widgets::helpers(project);
+        let state = widgets::state_struct(project, &name, &quote!());
+        let state_default = widgets::state_default(project, &name);
+        quote! {
+            #helpers
+            #[derive(Resource)]
+            #state
+            #state_default
+        }
+    }
+
+    /// The UI system, the plugin registering it, and `main`
+    fn emit_app_shell(&self) -> TokenStream {
+        let project = self.cx.project;
+        let has_fonts = project.fonts().next().is_some();
+        let font_setup = if has_fonts {
+            font_setup(&self.cx)
+        } else {
+            quote!()
+        };
+        let mut setup = Vec::new();
+        if uses_images(project) {
+            setup.push(quote!(egui_extras::install_image_loaders(ctx);));
+        }
+        if has_fonts {
+            setup.push(quote!(setup_fonts(ctx);));
+        }
+        // eframe's creation callback has no Bevy equivalent; run setup on the first frame
+        let (setup_param, setup) = if setup.is_empty() {
+            (quote!(), quote!())
+        } else {
+            (
+                quote!(mut initialised: Local<bool>,),
+                quote! {
+                    if !*initialised {
+                        #(#setup)*
+                        *initialised = true;
+                    }
+                },
+            )
+        };
+        quote! {
+            fn generated_ui_system(
+                mut contexts: EguiContexts,
+                mut state: ResMut<GeneratedState>,
+                #setup_param
+            ) {
+                let Ok(ctx) = contexts.ctx_mut() else {
+                    return;
+                };
+                #setup
+                generated_ui(ctx, &mut state);
+            }
+
+            #font_setup
+
+            pub struct GeneratedUiPlugin;
+
+            impl Plugin for GeneratedUiPlugin {
+                fn build(&self, app: &mut App) {
+                    app.init_resource::<GeneratedState>()
+                        .add_systems(EguiPrimaryContextPass, generated_ui_system);
+                }
+            }
+
+            fn main() {
+                App::new()
+                    .add_plugins(DefaultPlugins)
+                    .add_plugins(EguiPlugin::default())
+                    .add_plugins(GeneratedUiPlugin)
+                    .run();
+            }
+        }
+    }
+
+    fn emit_output(&self) -> String {
+        let mut out = String::new();
+
+        if self.cx.comments {
+            out.push_str(&banner(&[
+                "Generated by egui RAD GUI Builder",
+                "https://github.com/timschmidt/egui-rad-builder",
+            ]));
+            out.push('\n');
+            out.push_str("// Needs bevy 0.17 and bevy_egui 0.38, plus egui_extras and chrono\n");
+            out.push_str("// if the UI uses images or date pickers.\n");
+            if widgets::uses_access(self.cx.project) {
+                out.push_str(
+                    "// Accessible names need bevy_egui built with egui's `accesskit` feature.\n",
+                );
+            }
+            out.push('\n');
+        } else {
+            out.push_str("// --- generated by egui RAD GUI Builder ---\n");
+        }
+
+        let state = self.emit_state();
+        let ui_fn = ui_fn(self);
+        out.push_str(&pretty(quote! {
+            use bevy::prelude::*;
+            use bevy_egui::{EguiContexts, EguiPlugin, EguiPrimaryContextPass, egui};
+            #state
+            #ui_fn
+        }));
+        out.push('\n');
+
+        if self.cx.comments {
+            out.push_str(&banner(&["Bevy plugin and entry point"]));
+            out.push('\n');
+        }
+        out.push_str(&pretty(self.emit_app_shell()));
+
+        out
+    }
+}
+
+/// The wasm32 `main`: starts `create` on the page's canvas with `eframe::WebRunner`
+fn web_main(create: &TokenStream) -> TokenStream {
+    quote! {
+        #[cfg(target_arch = "wasm32")]
+        fn main() {
+            use eframe::wasm_bindgen::JsCast as _;
+            let web_options = eframe::WebOptions::default();
+            wasm_bindgen_futures::spawn_local(async {
+                let canvas = web_sys::window()
+                    .and_then(|w| w.document())
+                    .and_then(|d| d.get_element_by_id(#WEB_CANVAS_ID))
+                    .and_then(|c| c.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+                    .expect("no canvas element to draw into");
+                eframe::WebRunner::new()
+                    .start(canvas, web_options, #create)
+                    .await
+                    .expect("failed to start eframe");
+            });
+        }
+    }
+}
+
+/// `setup_fonts` registering every project font through `FontDefinitions`,
+/// matching `assets::install_font` in the builder
+fn font_setup(cx: &GenContext) -> TokenStream {
+    let fonts = cx.project.fonts().map(|a| {
+        let key = a.font_key();
+        let name = &a.name;
+        let path = format!("../{}", a.export_path());
+        quote! {
+            fonts.font_data.insert(
+                #key.to_owned(),
+                std::sync::Arc::new(egui::FontData::from_static(include_bytes!(#path))),
+            );
+            let mut family = vec![#key.to_owned()];
+            family.extend(fallback.iter().cloned());
+            fonts.families.insert(egui::FontFamily::Name(#name.into()), family);
+        }
+    });
+    quote! {
+        fn setup_fonts(ctx: &egui::Context) {
+            let mut fonts = egui::FontDefinitions::default();
+            let fallback = fonts
+                .families
+                .get(&egui::FontFamily::Proportional)
+                .cloned()
+                .unwrap_or_default();
+            #(#fonts)*
+            ctx.set_fonts(fonts);
+        }
+    }
+}
@@ -0,0 +1,1295 @@
+//! Code generation. A `CodeGenerator` backend turns the project into Rust
+//! source; the widget and layout emission it builds on is shared here.
+
+mod formats;
+mod tokens;
+mod widgets;
+
+pub(crate) use formats::{BevyPlugin, Component, SeparateFiles, SingleFile, UiOnly};
+pub(crate) use tokens::{SourceMap, comment, float, int, mark, pretty};
+
+use crate::{
+    layout::{self, Fallback, FlowItem, FlowLayout},
+    project::Project,
+    widget::{Anchor, DockArea, HAnchor, VAnchor, Widget, WidgetId, WidgetKind},
+};
+use egui::Vec2;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use serde::{Deserialize, Serialize};
+
+/// Code generation output format
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CodeGenFormat {
+    /// Single file with all code
+    #[default]
+    SingleFile,
+    /// Separate files: main.rs, state.rs, ui.rs
+    SeparateFiles,
+    /// Just the UI function (for embedding)
+    UiOnly,
+    /// A reusable struct drawing into any `&mut egui::Ui`
+    Component,
+    /// A Bevy plugin: state as a `Resource`, UI as a `bevy_egui` system
+    Bevy,
+}
+
+impl CodeGenFormat {
+    pub const fn display_name(&self) -> &'static str {
+        match self {
+            CodeGenFormat::SingleFile => "Single File",
+            CodeGenFormat::SeparateFiles => "Separate Files",
+            CodeGenFormat::UiOnly => "UI Function Only",
+            CodeGenFormat::Component => "Component Struct",
+            CodeGenFormat::Bevy => "Bevy Plugin",
+        }
+    }
+
+    /// The backend producing this format
+    pub(crate) fn generator<'a>(&self, cx: GenContext<'a>) -> Box<dyn CodeGenerator + 'a> {
+        match self {
+            CodeGenFormat::SingleFile => Box::new(SingleFile { cx }),
+            CodeGenFormat::SeparateFiles => Box::new(SeparateFiles {
+                main: SingleFile { cx },
+            }),
+            CodeGenFormat::UiOnly => Box::new(UiOnly { cx }),
+            CodeGenFormat::Component => Box::new(Component { cx }),
+            CodeGenFormat::Bevy => Box::new(BevyPlugin { cx }),
+        }
+    }
+}
+
+/// How widgets are placed in generated code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CodeGenLayout {
+    /// Every widget at its designed rect
+    #[default]
+    Absolute,
+    /// Rows and grids inferred from widget positions
+    Flow,
+}
+
+impl CodeGenLayout {
+    pub const fn display_name(&self) -> &'static str {
+        match self {
+            CodeGenLayout::Absolute => "Absolute",
+            CodeGenLayout::Flow => "Flow (rows/grids)",
+        }
+    }
+}
+
+/// Visibility of the generated component and its fields
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Visibility {
+    #[default]
+    Public,
+    Crate,
+    Private,
+}
+
+impl Visibility {
+    pub const fn display_name(&self) -> &'static str {
+        match self {
+            Visibility::Public => "pub",
+            Visibility::Crate => "pub(crate)",
+            Visibility::Private => "private",
+        }
+    }
+
+    fn tokens(&self) -> TokenStream {
+        match self {
+            Visibility::Public => quote!(pub),
+            Visibility::Crate => quote!(pub(crate)),
+            Visibility::Private => quote!(),
+        }
+    }
+}
+
+/// Naming for the `Component` format
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentOptions {
+    pub name: String,
+    pub visibility: Visibility,
+}
+
+impl Default for ComponentOptions {
+    fn default() -> Self {
+        Self {
+            name: "MyPanel".to_owned(),
+            visibility: Visibility::default(),
+        }
+    }
+}
+
+impl ComponentOptions {
+    /// `name` as an identifier, if it is a valid one
+    pub(crate) fn ident(&self) -> Option<Ident> {
+        syn::parse_str::<Ident>(self.name.trim()).ok()
+    }
+}
+
+/// What to generate code for
+#[derive(Clone, Copy)]
+pub(crate) struct GenContext<'a> {
+    pub(crate) project: &'a Project,
+    pub(crate) layout: CodeGenLayout,
+    /// Include explanatory comments
+    pub(crate) comments: bool,
+    /// Add a wasm32 entry point for trunk next to the native `main`
+    pub(crate) web: bool,
+    /// Struct name and visibility for the `Component` format
+    pub(crate) component: &'a ComponentOptions,
+}
+
+/// A code generation backend. The provided methods emit plain egui code;
+/// a backend overrides the pieces its target does differently.
+pub(crate) trait CodeGenerator {
+    /// The project and options being generated
+    fn context(&self) -> &GenContext<'_>;
+
+    /// `GeneratedState` with its `Default`, plus helper items the UI needs
+    fn emit_state(&self) -> TokenStream {
+        let project = self.context().project;
+        let helpers = widgets::helpers(project);
+        let name = format_ident!("GeneratedState");
+        let state = widgets::state_struct(project, &name, &quote!());
+        let state_default = widgets::state_default(project, &name);
+        quote!(#helpers #state #state_default)
+    }
+
+    /// Statements drawing a leaf widget into `ui`
+    fn emit_widget(&self, w: &Widget) -> Vec<TokenStream> {
+        widgets::widget_body(self, w)
+    }
+
+    /// Statements run when button or link `w` is clicked; none by default
+    fn emit_click(&self, _w: &Widget) -> TokenStream {
+        quote!()
+    }
+
+    /// Statements drawing a container and the widgets slotted into it
+    fn emit_container(&self, w: &Widget) -> Vec<TokenStream> {
+        widgets::container_body(self, w)
+    }
+
+    /// Items that run `generated_ui`, e.g. an app struct and `main`
+    fn emit_app_shell(&self) -> TokenStream;
+
+    /// The complete output, each widget's statements still bracketed by `mark`
+    fn emit_output(&self) -> String;
+
+    /// The complete output
+    fn generate(&self) -> String {
+        self.generate_mapped().0
+    }
+
+    /// The complete output and where each widget's code ended up in it
+    fn generate_mapped(&self) -> (String, SourceMap) {
+        SourceMap::strip(&self.emit_output())
+    }
+}
+
+/// Statements that draw `w` into the current `ui`. Where they end up (an
+/// absolute rect, a flow row, a grid cell) is decided by the caller.
+fn widget_code<G: CodeGenerator + ?Sized>(g: &G, w: &Widget) -> Vec<TokenStream> {
+    if w.kind.is_container() {
+        g.emit_container(w)
+    } else {
+        g.emit_widget(w)
+    }
+}
+
+/// `generated_ui`, drawing every panel and its widgets
+fn ui_fn<G: CodeGenerator + ?Sized>(g: &G) -> TokenStream {
+    let panels = panels_code(g, PanelHost::Context);
+    quote! {
+        fn generated_ui(ctx: &egui::Context, state: &mut GeneratedState) {
+            #panels
+        }
+    }
+}
+
+/// Where the generated panels are shown
+#[derive(Clone, Copy, PartialEq)]
+enum PanelHost {
+    /// Across the whole window, from `ctx`
+    Context,
+    /// Nested inside the `ui` handed to a component
+    Ui,
+}
+
+/// Every panel and its widgets, as statements
+fn panels_code<G: CodeGenerator + ?Sized>(g: &G, host: PanelHost) -> TokenStream {
+    use DockArea::*;
+    let mut top = Vec::new();
+    let mut bottom = Vec::new();
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let mut center = Vec::new();
+    let mut free = Vec::new();
+    // container children are emitted inside their container
+    for w in g
+        .context()
+        .project
+        .widgets
+        .iter()
+        .filter(|w| w.slot.is_none())
+    {
+        match w.area {
+            Top => top.push(w),
+            Bottom => bottom.push(w),
+            Left => left.push(w),
+            Right => right.push(w),
+            Center => center.push(w),
+            Free => free.push(w),
+        }
+    }
+
+    let panel = |flag: &str, panel: TokenStream, id: &str, widgets: &[&Widget]| {
+        let flag = format_ident!("{flag}");
+        let body = placed_code(g, widgets, &quote!(ui.min_rect().min));
+        let show = match host {
+            PanelHost::Context => quote!(#panel(#id).resizable(true).show(ctx, |ui| { #body });),
+            // ids are salted with the host ui's so two components don't clash
+            PanelHost::Ui => quote! {
+                #panel(ui.id().with(#id)).resizable(true).show_inside(ui, |ui| { #body });
+            },
+        };
+        quote! {
+            if state.#flag {
+                #show
+            }
+        }
+    };
+    let top = panel(
+        "enable_top",
+        quote!(egui::TopBottomPanel::top),
+        "gen_top",
+        &top,
+    );
+    let bottom = panel(
+        "enable_bottom",
+        quote!(egui::TopBottomPanel::bottom),
+        "gen_bottom",
+        &bottom,
+    );
+    let left = panel(
+        "enable_left",
+        quote!(egui::SidePanel::left),
+        "gen_left",
+        &left,
+    );
+    let right = panel(
+        "enable_right",
+        quote!(egui::SidePanel::right),
+        "gen_right",
+        &right,
+    );
+
+    // CENTER (+ FREE): use CentralPanel
+    center.extend(free);
+    let origin = quote!(canvas.min);
+    let center = match g.context().layout {
+        CodeGenLayout::Absolute => {
+            // live panel rect: fixed widgets sit at their designed offsets, anchored ones follow its edges
+            let note = if g.context().comments {
+                comment(&format!(
+                    "designed at {:.0}x{:.0}; anchored widgets follow the panel's edges",
+                    g.context().project.canvas_size.x,
+                    g.context().project.canvas_size.y
+                ))
+            } else {
+                quote!()
+            };
+            let body = placed_code(g, &center, &origin);
+            quote! {
+                #note
+                let canvas = ui.max_rect();
+                let _ = ui.allocate_painter(canvas.size(), egui::Sense::hover());
+                #body
+            }
+        }
+        CodeGenLayout::Flow => {
+            let flow = layout::infer(&center);
+            // the flow advances ui's cursor, so remember the panel rect for absolute leftovers
+            let canvas = if !flow.fallback.is_empty()
+                || center.iter().any(|w| w.kind == WidgetKind::Window)
+            {
+                quote!(let canvas = ui.max_rect();)
+            } else {
+                quote!()
+            };
+            let body = flow_code(g, &center, &flow, &origin);
+            quote!(#canvas #body)
+        }
+    };
+
+    let center = match host {
+        PanelHost::Context => quote!(egui::CentralPanel::default().show(ctx, |ui| { #center });),
+        PanelHost::Ui => quote!(egui::CentralPanel::default().show_inside(ui, |ui| { #center });),
+    };
+    quote! {
+        #top
+        #bottom
+        #left
+        #right
+        #center
+    }
+}
+
+/// Code placing `widgets` using the selected `CodeGenLayout`.
+fn placed_code<G: CodeGenerator + ?Sized>(
+    g: &G,
+    widgets: &[&Widget],
+    origin: &TokenStream,
+) -> TokenStream {
+    match g.context().layout {
+        CodeGenLayout::Absolute => {
+            let placed = widgets.iter().map(|w| absolute_code(g, w, origin));
+            quote!(#(#placed)*)
+        }
+        CodeGenLayout::Flow => flow_code(g, widgets, &layout::infer(widgets), origin),
+    }
+}
+
+/// Whether `placed_code` places any of `widgets` absolutely, against `origin`
+fn places_absolutely<G: CodeGenerator + ?Sized>(g: &G, widgets: &[&Widget]) -> bool {
+    match g.context().layout {
+        CodeGenLayout::Absolute => !widgets.is_empty(),
+        CodeGenLayout::Flow => {
+            widgets.iter().any(|w| w.kind == WidgetKind::Window)
+                || !layout::infer(widgets).fallback.is_empty()
+        }
+    }
+}
+
+/// Inferred rows and grids, then the widgets that stayed absolute.
+fn flow_code<G: CodeGenerator + ?Sized>(
+    g: &G,
+    widgets: &[&Widget],
+    flow: &FlowLayout,
+    origin: &TokenStream,
+) -> TokenStream {
+    let widget = |id: WidgetId| widgets.iter().copied().find(|w| w.id == id);
+    let cell = |id: WidgetId| widget(id).map(|w| cell_body(g, &[w]));
+    let space = |space: f32| {
+        if space >= 1.0 {
+            let space = float(space, 1);
+            quote!(ui.add_space(#space);)
+        } else {
+            quote!()
+        }
+    };
+    let mut out = Vec::new();
+    for (gap, item) in &flow.items {
+        out.push(space(*gap));
+        out.push(match item {
+            FlowItem::Row(row) if row.is_plain() => match widget(row.cells[0].1) {
+                Some(w) => {
+                    let body = widget_code(g, w);
+                    mark(w.id, quote!(#(#body)*))
+                }
+                None => quote!(),
+            },
+            FlowItem::Row(row) => {
+                let cells = row.cells.iter().map(|(lead, id)| {
+                    let (lead, cell) = (space(*lead), cell(*id));
+                    quote!(#lead #cell)
+                });
+                quote!(ui.horizontal(|ui| { #(#cells)* });)
+            }
+            FlowItem::Grid {
+                rows,
+                spacing,
+                indent,
+            } => {
+                let id = format!("flow_grid_{}", rows[0][0]);
+                let rows = rows.iter().map(|row| {
+                    let cells = row.iter().map(|id| cell(*id));
+                    quote!(#(#cells)* ui.end_row();)
+                });
+                let (sx, sy) = (float(spacing.x, 1), float(spacing.y, 1));
+                let grid = quote! {
+                    egui::Grid::new(#id)
+                        .spacing(egui::vec2(#sx, #sy))
+                        .show(ui, |ui| { #(#rows)* });
+                };
+                if *indent >= 1.0 {
+                    let indent = space(*indent);
+                    quote!(ui.horizontal(|ui| { #indent #grid });)
+                } else {
+                    grid
+                }
+            }
+        });
+    }
+    for &(id, reason) in &flow.fallback {
+        if let Some(w) = widget(id) {
+            if g.context().comments {
+                out.push(comment(&format!("kept absolute: {}", reason.reason())));
+            }
+            out.push(absolute_code(g, w, origin));
+        }
+    }
+    for w in widgets.iter().filter(|w| w.kind == WidgetKind::Window) {
+        out.push(absolute_code(g, w, origin));
+    }
+    quote!(#(#out)*)
+}
+
+/// Code for one row/grid cell. Several widgets, or a widget with a
+/// multi-statement body, get a vertical layout of their own.
+fn cell_body<G: CodeGenerator + ?Sized>(g: &G, widgets: &[&Widget]) -> TokenStream {
+    let bodies: Vec<Vec<TokenStream>> = widgets.iter().map(|w| widget_code(g, w)).collect();
+    let marked = widgets
+        .iter()
+        .zip(&bodies)
+        .map(|(w, body)| mark(w.id, quote!(#(#body)*)));
+    match bodies.as_slice() {
+        [] => quote!(ui.label("");),
+        [body] if body.len() == 1 => quote!(#(#marked)*),
+        _ => quote!(ui.vertical(|ui| { #(#marked)* });),
+    }
+}
+
+/// Rust expression for a widget's rect in generated code.
+///
+/// Fixed widgets are placed relative to `origin`. Anchored widgets are placed
+/// against the live `container` rect, using `design` (the container size the
+/// layout was drawn at) to turn edge distances into offsets.
+pub(crate) fn rect_expr(
+    w: &Widget,
+    origin: &TokenStream,
+    container: &TokenStream,
+    design: Vec2,
+) -> TokenStream {
+    let (x, y, wd, ht) = (w.pos.x, w.pos.y, w.size.x, w.size.y);
+    if w.anchor.is_fixed() {
+        let (x, y, wd, ht) = (float(x, 1), float(y, 1), float(wd, 1), float(ht, 1));
+        return quote!(egui::Rect::from_min_size(#origin + egui::vec2(#x, #y), egui::vec2(#wd, #ht)));
+    }
+    let plus = |edge: TokenStream, v: f32| {
+        let v = float(v, 1);
+        quote!(#container.#edge + #v)
+    };
+    let minus = |edge: TokenStream, v: f32| {
+        let v = float(v, 1);
+        quote!(#container.#edge - #v)
+    };
+    let (left, right) = match w.anchor.h {
+        HAnchor::Left => (plus(quote!(min.x), x), plus(quote!(min.x), x + wd)),
+        HAnchor::Right => (
+            minus(quote!(max.x), design.x - x),
+            minus(quote!(max.x), design.x - x - wd),
+        ),
+        HAnchor::Center => (
+            plus(quote!(center().x), x - design.x * 0.5),
+            plus(quote!(center().x), x + wd - design.x * 0.5),
+        ),
+        HAnchor::Stretch => (
+            plus(quote!(min.x), x),
+            minus(quote!(max.x), design.x - x - wd),
+        ),
+    };
+    let (top, bottom) = match w.anchor.v {
+        VAnchor::Top => (plus(quote!(min.y), y), plus(quote!(min.y), y + ht)),
+        VAnchor::Bottom => (
+            minus(quote!(max.y), design.y - y),
+            minus(quote!(max.y), design.y - y - ht),
+        ),
+        VAnchor::Center => (
+            plus(quote!(center().y), y - design.y * 0.5),
+            plus(quote!(center().y), y + ht - design.y * 0.5),
+        ),
+        VAnchor::Stretch => (
+            plus(quote!(min.y), y),
+            minus(quote!(max.y), design.y - y - ht),
+        ),
+    };
+    quote!(egui::Rect::from_min_max(egui::pos2(#left, #top), egui::pos2(#right, #bottom)))
+}
+
+/// Code placing `w` at its designed rect: relative to `origin`, or against
+/// the live `canvas` rect when it is anchored. Anchors outside the central
+/// panel, which has no `canvas`, are ignored.
+fn absolute_code<G: CodeGenerator + ?Sized>(
+    g: &G,
+    w: &Widget,
+    origin: &TokenStream,
+) -> TokenStream {
+    let fixed;
+    let w = if w.anchor.is_fixed() || w.is_anchorable() {
+        w
+    } else {
+        fixed = Widget {
+            anchor: Anchor::default(),
+            ..w.clone()
+        };
+        &fixed
+    };
+    let body = widget_code(g, w);
+    if w.kind == WidgetKind::Window {
+        let title = &w.props.text;
+        let (x, y) = (float(w.pos.x, 1), float(w.pos.y, 1));
+        let (wd, ht) = (float(w.size.x, 1), float(w.size.y, 1));
+        let open = format_ident!("window_{}_open", w.id.as_u64());
+        return mark(
+            w.id,
+            quote! {
+                egui::Window::new(#title)
+                    .default_pos(#origin + egui::vec2(#x, #y))
+                    .default_size(egui::vec2(#wd, #ht))
+                    .open(&mut state.#open)
+                    .show(ctx, |ui| { #(#body)* });
+            },
+        );
+    }
+    let rect = rect_expr(w, origin, &quote!(canvas), g.context().project.canvas_size);
+    mark(
+        w.id,
+        quote! {
+            ui.scope_builder(egui::UiBuilder::new().max_rect(#rect), |ui| { #(#body)* });
+        },
+    )
+}
+
+/// Widgets the flow layout couldn't place, per panel (Center and Free
+/// share one) and per tab.
+pub(crate) fn flow_fallbacks(project: &Project) -> Vec<(&Widget, Fallback)> {
+    let groups: Vec<Vec<&Widget>> = [
+        &[DockArea::Top][..],
+        &[DockArea::Bottom],
+        &[DockArea::Left],
+        &[DockArea::Right],
+        &[DockArea::Center, DockArea::Free],
+    ]
+    .iter()
+    .map(|areas| {
+        project
+            .widgets
+            .iter()
+            .filter(|w| w.slot.is_none() && areas.contains(&w.area))
+            .collect()
+    })
+    .collect();
+    let mut out = Vec::new();
+    for widgets in groups {
+        for (id, reason) in layout::infer(&widgets).fallback {
+            if let Some(w) = widgets.iter().find(|w| w.id == id) {
+                out.push((*w, reason));
+            }
+        }
+    }
+    // Tab contents drop their anchors, so only overlaps matter there
+    for tabs in project
+        .widgets
+        .iter()
+        .filter(|w| w.kind == WidgetKind::TabBar)
+    {
+        for tab in 0..tabs.props.items.len() {
+            let widgets: Vec<&Widget> = project
+                .widgets
+                .iter()
+                .filter(|w| w.slot.is_some_and(|s| s.parent == tabs.id && s.row == tab))
+                .collect();
+            for (id, reason) in layout::infer(&widgets).fallback {
+                if reason != Fallback::Overlapping {
+                    continue;
+                }
+                if let Some(w) = widgets.iter().find(|w| w.id == id) {
+                    out.push((*w, reason));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn uses_images(project: &Project) -> bool {
+    project
+        .widgets
+        .iter()
+        .any(|w| matches!(w.kind, WidgetKind::Image))
+}
+
+/// Cargo.toml for a generated project, with the wasm32-only deps if `web`
+pub(crate) fn cargo_toml(project: &Project, name: &str, web: bool) -> String {
+    let mut out = String::new();
+    out.push_str("[package]\n");
+    out.push_str(&format!("name = \"{}\"\n", name));
+    out.push_str("version = \"0.1.0\"\n");
+    out.push_str("edition = \"2021\"\n\n");
+    out.push_str("[dependencies]\n");
+    out.push_str("eframe = \"0.33\"\n");
+    out.push_str("egui = \"0.33\"\n");
+    if uses_images(project) {
+        out.push_str(
+            "egui_extras = { version = \"0.33\", features = [\"chrono\", \"file\", \"image\"] }\n",
+        );
+        out.push_str(
+            "image = { version = \"0.25\", default-features = false, features = [\"png\", \"jpeg\"] }\n",
+        );
+    } else {
+        out.push_str("egui_extras = { version = \"0.33\", features = [\"chrono\"] }\n");
+    }
+    out.push_str("chrono = \"0.4\"\n");
+    if web {
+        out.push_str("\n[target.'cfg(target_arch = \"wasm32\")'.dependencies]\n");
+        out.push_str("wasm-bindgen-futures = \"0.4\"\n");
+        out.push_str("web-sys = { version = \"0.3\", features = [\"HtmlCanvasElement\"] }\n");
+    }
+    out
+}
+
+/// index.html for `trunk serve`, with the canvas the web entry point looks up
+pub(crate) fn index_html(name: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1.0, user-scalable=no" />
+    <title>{name}</title>
+    <link data-trunk rel="rust" data-wasm-opt="2" />
+    <style>
+        html, body {{ margin: 0; padding: 0; width: 100%; height: 100%; overflow: hidden; }}
+        canvas {{ width: 100%; height: 100%; }}
+    </style>
+</head>
+<body>
+    <canvas id="{WEB_CANVAS_ID}"></canvas>
+</body>
+</html>
+"#
+    )
+}
+
+/// The canvas element generated web apps draw into
+const WEB_CANVAS_ID: &str = "the_canvas_id";
+
+/// A comment block framed by `// ===` rules
+fn banner(lines: &[&str]) -> String {
+    let rule = format!("// {}\n", "=".repeat(77));
+    let mut out = rule.clone();
+    for line in lines {
+        out.push_str(&format!("// {line}\n"));
+    }
+    out.push_str(&rule);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::{Asset, AssetId, AssetKind};
+    use crate::widget::Slot;
+    use egui::{pos2, vec2};
+    use std::collections::BTreeSet;
+    use std::path::Path;
+    use syn::visit::{self, Visit};
+
+    const FORMATS: [CodeGenFormat; 5] = [
+        CodeGenFormat::SingleFile,
+        CodeGenFormat::SeparateFiles,
+        CodeGenFormat::UiOnly,
+        CodeGenFormat::Component,
+        CodeGenFormat::Bevy,
+    ];
+    const LAYOUTS: [CodeGenLayout; 2] = [CodeGenLayout::Absolute, CodeGenLayout::Flow];
+
+    /// A project and the code generation settings to run it through
+    struct Case {
+        project: Project,
+        format: CodeGenFormat,
+        layout: CodeGenLayout,
+        comments: bool,
+        web: bool,
+        component: ComponentOptions,
+    }
+
+    impl Case {
+        fn new(project: Project) -> Self {
+            Self {
+                project,
+                format: CodeGenFormat::default(),
+                layout: CodeGenLayout::default(),
+                comments: true,
+                web: false,
+                component: ComponentOptions::default(),
+            }
+        }
+
+        fn generate(&self) -> String {
+            let cx = GenContext {
+                project: &self.project,
+                layout: self.layout,
+                comments: self.comments,
+                web: self.web,
+                component: &self.component,
+            };
+            self.format.generator(cx).generate()
+        }
+    }
+
+    /// A project with one widget of every kind stacked down the canvas, a
+    /// child in a grid cell and in a tab, one widget per side panel, and
+    /// accessible names on widgets whose responses are reached differently.
+    fn every_widget_project() -> Project {
+        let mut project = Project::default();
+        let mut y = 10.0;
+        let mut id = 0;
+        for &kind in WidgetKind::all() {
+            id += 1;
+            let mut w = Widget::for_test(id, kind, pos2(10.0, y));
+            if matches!(
+                kind,
+                WidgetKind::ImageTextButton | WidgetKind::ComboBox | WidgetKind::DragValue
+            ) {
+                w.props.access_label = format!("Accessible {}", kind.display_name());
+                w.props.access_description = "Read out by screen readers".into();
+            }
+            project.widgets.push(w);
+            y += kind.default_size().y + 10.0;
+        }
+        let container = |kind| {
+            project
+                .widgets
+                .iter()
+                .find(|w| w.kind == kind)
+                .map(|w| w.id)
+                .unwrap()
+        };
+        let (grid, tabs) = (container(WidgetKind::Grid), container(WidgetKind::TabBar));
+        for (parent, kind) in [(grid, WidgetKind::Checkbox), (tabs, WidgetKind::TextEdit)] {
+            id += 1;
+            let mut child = Widget::for_test(id, kind, pos2(10.0, y));
+            child.slot = Some(Slot {
+                parent,
+                row: 0,
+                col: 0,
+            });
+            project.widgets.push(child);
+        }
+        for area in [
+            DockArea::Top,
+            DockArea::Bottom,
+            DockArea::Left,
+            DockArea::Right,
+        ] {
+            id += 1;
+            let mut w = Widget::for_test(id, WidgetKind::Slider, pos2(10.0, 10.0));
+            w.area = area;
+            // anchors from a hand-edited file, which only apply centrally
+            if area == DockArea::Left {
+                w.anchor.h = HAnchor::Stretch;
+            }
+            project.widgets.push(w);
+        }
+        id += 1;
+        let mut stretched = Widget::for_test(id, WidgetKind::Button, pos2(400.0, 10.0));
+        stretched.anchor = Anchor {
+            h: HAnchor::Stretch,
+            v: VAnchor::Bottom,
+        };
+        project.widgets.push(stretched);
+        project.panel_top_enabled = true;
+        project.panel_bottom_enabled = true;
+        project.panel_left_enabled = true;
+        project.panel_right_enabled = true;
+        project
+    }
+
+    /// The Rust part of the generated output
+    fn rust_source(format: CodeGenFormat, code: &str) -> &str {
+        match format {
+            CodeGenFormat::SeparateFiles => {
+                let marker = "// FILE: src/main.rs\n";
+                let start = code.find(marker).expect("no src/main.rs section");
+                let rest = &code[start + marker.len()..];
+                rest.split_once('\n').map_or(rest, |(_, body)| body)
+            }
+            _ => code,
+        }
+    }
+
+    /// Fields of the state struct `state`: declared, initialised in
+    /// `Default`, and read through `state.` anywhere in the file.
+    #[derive(Default)]
+    struct StateFields {
+        state: String,
+        declared: BTreeSet<String>,
+        initialised: BTreeSet<String>,
+        used: BTreeSet<String>,
+        in_default: bool,
+    }
+
+    impl<'ast> Visit<'ast> for StateFields {
+        fn visit_item_struct(&mut self, s: &'ast syn::ItemStruct) {
+            if s.ident == self.state {
+                let names = s.fields.iter().filter_map(|f| f.ident.as_ref());
+                self.declared.extend(names.map(|i| i.to_string()));
+            }
+            visit::visit_item_struct(self, s);
+        }
+
+        fn visit_item_impl(&mut self, i: &'ast syn::ItemImpl) {
+            let outer = self.in_default;
+            self.in_default =
+                matches!(&*i.self_ty, syn::Type::Path(p) if p.path.is_ident(&self.state));
+            visit::visit_item_impl(self, i);
+            self.in_default = outer;
+        }
+
+        fn visit_expr_struct(&mut self, e: &'ast syn::ExprStruct) {
+            if self.in_default && e.path.is_ident("Self") {
+                for f in &e.fields {
+                    if let syn::Member::Named(name) = &f.member {
+                        self.initialised.insert(name.to_string());
+                    }
+                }
+            }
+            visit::visit_expr_struct(self, e);
+        }
+
+        fn visit_expr_field(&mut self, e: &'ast syn::ExprField) {
+            if let (syn::Expr::Path(base), syn::Member::Named(name)) = (&*e.base, &e.member)
+                && base.path.is_ident("state")
+            {
+                self.used.insert(name.to_string());
+            }
+            visit::visit_expr_field(self, e);
+        }
+
+        // `format!` and friends hide their arguments from the syntax tree
+        fn visit_macro(&mut self, m: &'ast syn::Macro) {
+            use syn::parse::Parser;
+            let args = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+            if let Ok(exprs) = args.parse2(m.tokens.clone()) {
+                for e in &exprs {
+                    self.visit_expr(e);
+                }
+            }
+            visit::visit_macro(self, m);
+        }
+    }
+
+    #[test]
+    fn test_rect_expr() {
+        let design = vec2(400.0, 300.0);
+        let (origin, canvas) = (quote!(canvas.min), quote!(canvas));
+        let mut w = Widget::for_test(1, WidgetKind::Button, pos2(10.0, 20.0));
+        w.size = vec2(100.0, 30.0);
+        assert_eq!(
+            rect_expr(&w, &origin, &canvas, design).to_string(),
+            quote!(egui::Rect::from_min_size(
+                canvas.min + egui::vec2(10.0, 20.0),
+                egui::vec2(100.0, 30.0)
+            ))
+            .to_string()
+        );
+
+        w.anchor = Anchor {
+            h: HAnchor::Stretch,
+            v: VAnchor::Bottom,
+        };
+        assert_eq!(
+            rect_expr(&w, &origin, &canvas, design).to_string(),
+            quote!(egui::Rect::from_min_max(
+                egui::pos2(canvas.min.x + 10.0, canvas.max.y - 280.0),
+                egui::pos2(canvas.max.x - 290.0, canvas.max.y - 250.0)
+            ))
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_anchors_ignored_outside_the_central_panel() {
+        let mut project = Project::default();
+        let mut w = Widget::for_test(1, WidgetKind::Button, pos2(10.0, 20.0));
+        w.area = DockArea::Left;
+        w.anchor = Anchor {
+            h: HAnchor::Stretch,
+            v: VAnchor::Bottom,
+        };
+        project.widgets.push(w);
+        project.panel_left_enabled = true;
+        let mut case = Case::new(project);
+        for layout in LAYOUTS {
+            case.layout = layout;
+            let code = case.generate();
+            assert!(!code.contains("canvas.max"), "{code}");
+            assert!(!code.contains("kept absolute"), "{code}");
+            assert!(code.contains("egui::vec2(160.0, 32.0)"), "{code}");
+        }
+    }
+
+    #[test]
+    fn test_tab_origin_only_for_absolute_children() {
+        let mut project = Project::default();
+        project
+            .widgets
+            .push(Widget::for_test(1, WidgetKind::TabBar, pos2(10.0, 10.0)));
+        let mut child = Widget::for_test(2, WidgetKind::Label, pos2(20.0, 50.0));
+        // mentioning it in a string doesn't count
+        child.props.text = "tab_origin".into();
+        child.slot = Some(Slot {
+            parent: WidgetId::new(1),
+            row: 0,
+            col: 0,
+        });
+        project.widgets.push(child);
+        let mut case = Case::new(project);
+        case.layout = CodeGenLayout::Flow;
+        assert!(!case.generate().contains("let tab_origin"));
+        case.layout = CodeGenLayout::Absolute;
+        assert!(case.generate().contains("let tab_origin"));
+    }
+
+    #[test]
+    fn test_generated_code_parses_and_declares_state() {
+        let mut case = Case::new(every_widget_project());
+        for layout in LAYOUTS {
+            for format in FORMATS {
+                case.layout = layout;
+                case.format = format;
+                let code = case.generate();
+                let file = syn::parse_file(rust_source(format, &code)).unwrap_or_else(|e| {
+                    panic!("{format:?}/{layout:?} output doesn't parse: {e}\n{code}")
+                });
+                if format == CodeGenFormat::SingleFile {
+                    case.web = true;
+                    let web = case.generate();
+                    case.web = false;
+                    syn::parse_file(&web).unwrap_or_else(|e| {
+                        panic!("{layout:?} web output doesn't parse: {e}\n{web}")
+                    });
+                }
+                let state = match format {
+                    CodeGenFormat::Component => "MyPanel",
+                    _ => "GeneratedState",
+                };
+                let mut fields = StateFields {
+                    state: state.to_owned(),
+                    ..Default::default()
+                };
+                fields.visit_file(&file);
+                let undeclared: Vec<_> = fields.used.difference(&fields.declared).collect();
+                assert!(
+                    undeclared.is_empty(),
+                    "{format:?}/{layout:?} uses undeclared state fields {undeclared:?}"
+                );
+                if format != CodeGenFormat::UiOnly {
+                    assert_eq!(
+                        fields.declared, fields.initialised,
+                        "{format:?}/{layout:?} Default doesn't match {state}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_component_naming() {
+        let mut case = Case::new(every_widget_project());
+        case.format = CodeGenFormat::Component;
+        case.component = ComponentOptions {
+            name: " Dashboard ".to_owned(),
+            visibility: Visibility::Crate,
+        };
+        let code = case.generate();
+        assert!(code.contains("pub(crate) struct Dashboard {"));
+        assert!(code.contains("pub(crate) struct DashboardResponse {"));
+        assert!(
+            code.contains("pub(crate) fn ui(&mut self, ui: &mut egui::Ui) -> DashboardResponse")
+        );
+        assert!(code.contains("impl egui::Widget for &mut Dashboard {"));
+        // every button reports its clicks
+        let buttons = case
+            .project
+            .widgets
+            .iter()
+            .filter(|w| w.kind == WidgetKind::Button);
+        for w in buttons {
+            assert!(code.contains(&format!("pub(crate) clicked_{}: bool", w.id)));
+        }
+
+        case.component.name = "fn".to_owned();
+        case.component.visibility = Visibility::Private;
+        let code = case.generate();
+        assert!(code.contains("\nstruct MyPanel {"));
+        assert!(code.contains("\nimpl egui::Widget for &mut MyPanel {"));
+    }
+
+    #[test]
+    fn test_fonts_keyed_by_asset_id() {
+        let mut project = Project::default();
+        project.assets.push(Asset {
+            id: AssetId::new(3),
+            kind: AssetKind::Font,
+            name: "Hack".into(),
+            path: "fonts/Hack.ttf".into(),
+            data: None,
+        });
+        let code = Case::new(project).generate();
+        assert!(code.contains("vec![\"asset_3\".to_owned()]"), "{code}");
+        assert!(
+            code.contains("egui::FontFamily::Name(\"Hack\".into())"),
+            "{code}"
+        );
+        assert!(!code.contains(".push("), "{code}");
+    }
+
+    #[test]
+    fn test_bevy_plugin() {
+        let mut case = Case::new(every_widget_project());
+        case.format = CodeGenFormat::Bevy;
+        let code = case.generate();
+        let file = syn::parse_file(&code).unwrap_or_else(|e| panic!("doesn't parse: {e}\n{code}"));
+        let derives_resource = |s: &syn::ItemStruct| {
+            s.attrs.iter().any(|a| {
+                a.path().is_ident("derive")
+                    && a.parse_args::<syn::Path>()
+                        .is_ok_and(|p| p.is_ident("Resource"))
+            })
+        };
+        let mut resource = false;
+        let mut plugin = false;
+        let mut system = None;
+        for item in &file.items {
+            match item {
+                syn::Item::Struct(s) if s.ident == "GeneratedState" => {
+                    resource = derives_resource(s);
+                }
+                syn::Item::Impl(i) => {
+                    let is_plugin = i
+                        .trait_
+                        .as_ref()
+                        .is_some_and(|(_, path, _)| path.is_ident("Plugin"));
+                    plugin |= is_plugin
+                        && matches!(&*i.self_ty, syn::Type::Path(p) if p.path.is_ident("GeneratedUiPlugin"));
+                }
+                syn::Item::Fn(f) if f.sig.ident == "generated_ui_system" => {
+                    system = Some(quote!(#f).to_string());
+                }
+                _ => {}
+            }
+        }
+        assert!(resource, "GeneratedState isn't a Resource");
+        assert!(plugin, "no Plugin impl for GeneratedUiPlugin");
+        let system = system.expect("no generated_ui_system");
+        assert!(system.contains("EguiContexts"));
+        assert!(system.contains("ResMut < GeneratedState >"));
+    }
+
+    #[test]
+    fn test_source_map_covers_every_widget() {
+        for format in FORMATS {
+            for layout in LAYOUTS {
+                let mut case = Case::new(every_widget_project());
+                case.format = format;
+                case.layout = layout;
+                let cx = GenContext {
+                    project: &case.project,
+                    layout,
+                    comments: true,
+                    web: false,
+                    component: &case.component,
+                };
+                let (code, map) = format.generator(cx).generate_mapped();
+                assert_eq!(code, case.generate());
+                for w in &case.project.widgets {
+                    let span = map
+                        .span(w.id)
+                        .unwrap_or_else(|| panic!("{format:?} {layout:?}: no code for #{}", w.id));
+                    assert_eq!(map.widget_at(span.start), Some(w.id));
+                    // a child's code is inside its container's
+                    if let Some(slot) = w.slot {
+                        let parent = map.span(slot.parent).unwrap();
+                        assert!(parent.start < span.start && span.end <= parent.end);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_import_round_trip() {
+        for format in FORMATS {
+            for layout in LAYOUTS {
+                let mut case = Case::new(every_widget_project());
+                case.format = format;
+                case.layout = layout;
+                let code = case.generate();
+                let imported = crate::import::import_code(&code)
+                    .unwrap_or_else(|e| panic!("{format:?} {layout:?}: {e}"));
+                assert!(
+                    imported.warnings.is_empty(),
+                    "{format:?} {layout:?}: {:#?}",
+                    imported.warnings
+                );
+                let kinds = |p: &Project| {
+                    let mut kinds: Vec<_> = p.widgets.iter().map(|w| (w.kind, w.area)).collect();
+                    kinds.sort_by_key(|(k, a)| (k.display_name(), format!("{a:?}")));
+                    kinds
+                };
+                assert_eq!(
+                    kinds(&imported.project),
+                    kinds(&case.project),
+                    "{format:?} {layout:?}"
+                );
+                // absolute layouts keep every position, so regenerate identically;
+                // UI-only output has no `Default` to recover initial values from
+                if layout == CodeGenLayout::Absolute && format != CodeGenFormat::UiOnly {
+                    let again = Case {
+                        project: imported.project,
+                        ..case
+                    };
+                    assert_eq!(again.generate(), code, "{format:?}");
+                }
+            }
+        }
+    }
+
+    /// Snapshot name for a widget kind, e.g. "Menu Button" -> `menu_button`
+    fn snake_name(kind: WidgetKind) -> String {
+        let lower = kind.display_name().to_lowercase();
+        let words: Vec<&str> = lower
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|s| !s.is_empty())
+            .collect();
+        words.join("_")
+    }
+
+    /// Fixture projects paired with the snapshot they're checked against
+    fn snapshot_cases() -> Vec<(String, Case)> {
+        let mut cases = Vec::new();
+        for &kind in WidgetKind::all() {
+            let mut case = Case::new(Project::default());
+            case.project
+                .widgets
+                .push(Widget::for_test(1, kind, pos2(20.0, 20.0)));
+            cases.push((format!("widget_{}", snake_name(kind)), case));
+        }
+
+        // One widget per dock area, with the side panels on and off
+        let docks = |panels: bool, comments: bool| {
+            let mut case = Case::new(Project::default());
+            let areas = [
+                (DockArea::Top, WidgetKind::Label),
+                (DockArea::Bottom, WidgetKind::ProgressBar),
+                (DockArea::Left, WidgetKind::Button),
+                (DockArea::Right, WidgetKind::Checkbox),
+                (DockArea::Center, WidgetKind::TextEdit),
+                (DockArea::Free, WidgetKind::Slider),
+            ];
+            for (i, (area, kind)) in areas.into_iter().enumerate() {
+                let mut w =
+                    Widget::for_test(i as u64 + 1, kind, pos2(10.0, 10.0 + 50.0 * i as f32));
+                w.area = area;
+                case.project.widgets.push(w);
+            }
+            case.project.panel_top_enabled = panels;
+            case.project.panel_bottom_enabled = panels;
+            case.project.panel_left_enabled = panels;
+            case.project.panel_right_enabled = panels;
+            case.comments = comments;
+            case
+        };
+        cases.push(("docks_panels_off".to_owned(), docks(false, true)));
+        cases.push(("docks_panels_on".to_owned(), docks(true, true)));
+        cases.push(("docks_comments_off".to_owned(), docks(true, false)));
+        let mut web = docks(false, true);
+        web.format = CodeGenFormat::SeparateFiles;
+        web.web = true;
+        cases.push(("web_separate_files".to_owned(), web));
+
+        // Every widget together in each format and layout
+        for layout in LAYOUTS {
+            for format in FORMATS {
+                let mut case = Case::new(every_widget_project());
+                case.layout = layout;
+                case.format = format;
+                let name = format!("all_{format:?}_{layout:?}").to_lowercase();
+                cases.push((name, case));
+            }
+        }
+        cases
+    }
+
+    /// Compares generated code against `src/snapshots/<case>.snap`. Run with
+    /// `UPDATE_SNAPSHOTS=1` to rewrite the files after an intended change and
+    /// review the result with `git diff src/snapshots`.
+    #[test]
+    fn test_codegen_snapshots() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/snapshots");
+        let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+        let mut failures = Vec::new();
+        for (name, case) in snapshot_cases() {
+            let path = dir.join(format!("{name}.snap"));
+            let actual = case.generate();
+            if update {
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(&path, &actual).unwrap();
+                continue;
+            }
+            let expected = std::fs::read_to_string(&path).unwrap_or_default();
+            if expected != actual {
+                let line = expected
+                    .lines()
+                    .zip(actual.lines())
+                    .position(|(e, a)| e != a)
+                    .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+                failures.push(format!(
+                    "{name}: differs at line {}\n  expected: {:?}\n  actual:   {:?}",
+                    line + 1,
+                    expected.lines().nth(line).unwrap_or("<end>"),
+                    actual.lines().nth(line).unwrap_or("<end>"),
+                ));
+            }
+        }
+        assert!(
+            failures.is_empty(),
+            "codegen snapshots changed (rerun with UPDATE_SNAPSHOTS=1 to accept):\n{}",
+            failures.join("\n")
+        );
+    }
+
+    /// Builds the generated project with `cargo check --offline`. Needs the
+    /// egui crates in the local registry, so run it with `--ignored`.
+    #[test]
+    #[ignore]
+    fn test_generated_code_compiles() {
+        let mut case = Case::new(every_widget_project());
+        let dir = std::env::temp_dir().join("egui-rad-builder-codegen-check");
+        std::fs::create_dir_all(dir.join("src/bin")).unwrap();
+        let manifest = cargo_toml(&case.project, "codegen-check", true) + "\n[workspace]\n";
+        std::fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+        // only the native half of the web entry point is checked here
+        case.web = true;
+        std::fs::write(dir.join("src/main.rs"), case.generate()).unwrap();
+        case.web = false;
+        case.layout = CodeGenLayout::Flow;
+        std::fs::write(dir.join("src/bin/flow.rs"), case.generate()).unwrap();
+        case.format = CodeGenFormat::Component;
+        let host = r#"
+fn main() -> eframe::Result<()> {
+    let mut panel = MyPanel::default();
+    eframe::run_simple_native("component", Default::default(), move |ctx, _frame| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.add(&mut panel);
+        });
+    })
+}
+"#;
+        std::fs::write(dir.join("src/bin/component.rs"), case.generate() + host).unwrap();
+        let status = std::process::Command::new(env!("CARGO"))
+            .args(["check", "--offline", "--quiet", "--bins"])
+            .current_dir(&dir)
+            .status()
+            .expect("failed to run cargo");
+        assert!(
+            status.success(),
+            "generated project in {dir:?} doesn't compile"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Building blocks for generated code: it is assembled as `quote` token
+//! streams and printed with `prettyplease`, so the output is always
+//! well-formed and consistently formatted.
+
+use crate::widget::WidgetId;
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use std::ops::Range;
+
+/// Name of the placeholder macro standing in for a `//` comment, which token
+/// streams can't carry. `pretty` turns each one back into a comment line.
+const COMMENT_MACRO: &str = "__comment";
+
+/// Placeholder macros bracketing the statements a widget produced.
+/// `pretty` turns each into a `MARK` line that `SourceMap::strip` removes.
+const START_MACRO: &str = "__widget_start";
+const END_MACRO: &str = "__widget_end";
+
+/// Starts a line recording a widget boundary in printed code; followed by
+/// `+id` or `-id`. Can't occur in generated code, which escapes control
+/// characters in its string literals.
+const MARK: char = '\u{1}';
+
+/// A `// text` comment statement
+pub(crate) fn comment(text: &str) -> TokenStream {
+    quote!(__comment!(#text);)
+}
+
+/// `stmts` bracketed so the lines they print to can be traced to `id`
+pub(crate) fn mark(id: WidgetId, stmts: TokenStream) -> TokenStream {
+    let id = Literal::u64_unsuffixed(id.as_u64());
+    quote!(__widget_start!(#id); #stmts __widget_end!(#id);)
+}
+
+/// `x` rounded to `decimals` places, as an unsuffixed float literal
+pub(crate) fn float(x: f32, decimals: i32) -> TokenStream {
+    let scale = 10f64.powi(decimals);
+    let rounded = (f64::from(x.abs()) * scale).round() / scale;
+    let lit = Literal::f64_unsuffixed(rounded);
+    if x < 0.0 && rounded != 0.0 {
+        quote!(-#lit)
+    } else {
+        quote!(#lit)
+    }
+}
+
+/// An unsuffixed integer literal
+pub(crate) fn int(n: usize) -> Literal {
+    Literal::usize_unsuffixed(n)
+}
+
+/// Pretty-print a sequence of items, separated by blank lines.
+pub(crate) fn pretty(items: TokenStream) -> String {
+    let file: syn::File = match syn::parse2(items.clone()) {
+        Ok(file) => file,
+        // A generator bug: show what was produced rather than nothing
+        Err(e) => return format!("// generated code doesn't parse: {e}\n{items}\n"),
+    };
+    let mut out = String::new();
+    for (i, item) in file.items.into_iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let single = syn::File {
+            shebang: None,
+            attrs: Vec::new(),
+            items: vec![item],
+        };
+        let text = prettyplease::unparse(&single);
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            let mut line = line.to_owned();
+            // Long comments get their argument wrapped onto lines of its own
+            if line.trim_start() == format!("{COMMENT_MACRO}!(") {
+                for next in lines.by_ref() {
+                    line.push_str(next.trim());
+                    if next.trim() == ");" {
+                        break;
+                    }
+                }
+            }
+            if let Some(marker) = marker(&line) {
+                out.push_str(&marker);
+            } else {
+                out.push_str(&uncomment(&line).unwrap_or(line));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// `__comment!("text");` back to `// text`, keeping the indentation
+fn uncomment(line: &str) -> Option<String> {
+    let code = line.trim_start();
+    let arg = code
+        .strip_prefix(COMMENT_MACRO)?
+        .strip_prefix("!(")?
+        .strip_suffix(");")?;
+    let text = syn::parse_str::<syn::LitStr>(arg).ok()?.value();
+    Some(format!("{}// {text}", &line[..line.len() - code.len()]))
+}
+
+/// `__widget_start!(3);` to `MARK` `+3`, `__widget_end!(3);` to `MARK` `-3`
+fn marker(line: &str) -> Option<String> {
+    let code = line.trim();
+    let (sign, rest) = if let Some(rest) = code.strip_prefix(START_MACRO) {
+        ('+', rest)
+    } else {
+        ('-', code.strip_prefix(END_MACRO)?)
+    };
+    let id: u64 = rest.strip_prefix("!(")?.strip_suffix(");")?.parse().ok()?;
+    Some(format!("{MARK}{sign}{id}"))
+}
+
+/// Which bytes of the generated code each widget produced
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SourceMap {
+    /// Whole lines per widget; a container's range encloses its children's
+    spans: Vec<(WidgetId, Range<usize>)>,
+}
+
+impl SourceMap {
+    /// Remove the `MARK` lines from printed code, recording where each
+    /// widget's code ended up.
+    pub(crate) fn strip(marked: &str) -> (String, Self) {
+        let mut code = String::with_capacity(marked.len());
+        let mut open = Vec::new();
+        let mut spans = Vec::new();
+        for line in marked.split_inclusive('\n') {
+            let Some(rest) = line.strip_prefix(MARK) else {
+                code.push_str(line);
+                continue;
+            };
+            let rest = rest.trim_end();
+            if let Some(id) = rest.strip_prefix('+').and_then(|id| id.parse().ok()) {
+                open.push((WidgetId::new(id), code.len()));
+            } else if let Some((id, start)) = open.pop() {
+                spans.push((id, start..code.len()));
+            }
+        }
+        spans.sort_by_key(|(_, r)| r.start);
+        (code, Self { spans })
+    }
+
+    /// The first range `id` produced
+    pub(crate) fn span(&self, id: WidgetId) -> Option<Range<usize>> {
+        self.spans
+            .iter()
+            .find(|(w, _)| *w == id)
+            .map(|(_, r)| r.clone())
+    }
+
+    /// The innermost widget whose code contains byte `at`
+    pub(crate) fn widget_at(&self, at: usize) -> Option<WidgetId> {
+        self.spans
+            .iter()
+            .filter(|(_, r)| r.contains(&at))
+            .min_by_key(|(_, r)| r.len())
+            .map(|(id, _)| *id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_float() {
+        assert_eq!(float(10.0, 1).to_string(), "10.0");
+        assert_eq!(float(0.1 + 0.2, 1).to_string(), "0.3");
+        assert_eq!(float(1.23456, 3).to_string(), "1.235");
+        assert_eq!(float(-5.0, 1).to_string(), "- 5.0");
+        assert_eq!(float(-0.01, 1).to_string(), "0.0");
+    }
+
+    #[test]
+    fn test_source_map() {
+        let (a, b) = (WidgetId::new(1), WidgetId::new(2));
+        let label = mark(b, quote!(ui.label("x");));
+        let group = mark(a, quote!(ui.group(|ui| { #label });));
+        let (code, map) = SourceMap::strip(&pretty(quote! {
+            fn f(ui: &mut egui::Ui) { #group ui.separator(); }
+        }));
+        assert_eq!(
+            code,
+            "fn f(ui: &mut egui::Ui) {\n    ui.group(|ui| {\n        ui.label(\"x\");\n    });\n    ui.separator();\n}\n"
+        );
+        let line = |n: usize| {
+            let start: usize = code.split_inclusive('\n').take(n).map(str::len).sum();
+            start..start + code.split_inclusive('\n').nth(n).unwrap().len()
+        };
+        assert_eq!(map.span(a), Some(line(1).start..line(3).end));
+        assert_eq!(map.span(b), Some(line(2)));
+        assert_eq!(map.widget_at(line(1).start), Some(a));
+        assert_eq!(map.widget_at(line(2).start + 4), Some(b));
+        assert_eq!(map.widget_at(line(4).start), None);
+    }
+
+    #[test]
+    fn test_pretty_comments() {
+        let note = comment("kept \"absolute\"");
+        let long = comment(&"long ".repeat(30));
+        let code = pretty(quote! {
+            fn a(ui: &mut egui::Ui) { #note ui.label("x"); #long }
+            fn b() {}
+        });
+        assert_eq!(
+            code,
+            format!(
+                "fn a(ui: &mut egui::Ui) {{\n    // kept \"absolute\"\n    ui.label(\"x\");\n    // {}\n}}\n\nfn b() {{}}\n",
+                "long ".repeat(30)
+            )
+        );
+    }
+}
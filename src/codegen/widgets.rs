@@ -0,0 +1,666 @@
+//! The egui code each widget kind turns into, and the state it keeps.
+
+use super::{CodeGenerator, cell_body, comment, float, int, placed_code, places_absolutely};
+use crate::{
+    project::Project,
+    widget::{Anchor, Slot, Widget, WidgetKind},
+};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+
+/// Whether any widget's code goes through `gen_accessible`
+pub(crate) fn uses_access(project: &Project) -> bool {
+    project.widgets.iter().any(|w| access(w).is_some())
+}
+
+/// Functions the widget code calls: `GenTreeNode` and its drawing helper if
+/// the project has a tree, and `gen_accessible` if a widget needs it
+pub(crate) fn helpers(project: &Project) -> TokenStream {
+    let has_tree = project
+        .widgets
+        .iter()
+        .any(|w| matches!(w.kind, WidgetKind::Tree));
+    let accessible = if uses_access(project) {
+        quote! {
+            fn gen_accessible(response: egui::Response, label: &str, description: &str) -> egui::Response {
+                response.ctx.accesskit_node_builder(response.id, |node| {
+                    if !label.is_empty() {
+                        node.set_label(label);
+                    }
+                    if !description.is_empty() {
+                        node.set_description(description);
+                    }
+                });
+                response
+            }
+        }
+    } else {
+        quote!()
+    };
+    if !has_tree {
+        return accessible;
+    }
+    quote! {
+        #accessible
+        #[derive(Clone)]
+        struct GenTreeNode {
+            label: String,
+            children: Vec<GenTreeNode>,
+        }
+
+        fn gen_show_tree(ui: &mut egui::Ui, nodes: &[GenTreeNode]) {
+            for n in nodes {
+                if n.children.is_empty() {
+                    ui.label(&n.label);
+                } else {
+                    ui.collapsing(&n.label, |ui| gen_show_tree(ui, &n.children));
+                }
+            }
+        }
+    }
+}
+
+/// `GeneratedState` fields: name, type and initial value
+fn state_fields(project: &Project) -> Vec<(Ident, TokenStream, TokenStream)> {
+    let mut fields = Vec::new();
+    for (name, on) in [
+        ("enable_top", project.panel_top_enabled),
+        ("enable_bottom", project.panel_bottom_enabled),
+        ("enable_left", project.panel_left_enabled),
+        ("enable_right", project.panel_right_enabled),
+    ] {
+        fields.push((format_ident!("{name}"), quote!(bool), quote!(#on)));
+    }
+    for w in &project.widgets {
+        let text = &w.props.text;
+        let field = |prefix: &str| format_ident!("{prefix}_{}", w.id.as_u64());
+        let (name, ty, init) = match w.kind {
+            WidgetKind::TextEdit => (field("text"), quote!(String), quote!(#text.to_owned())),
+            WidgetKind::Checkbox => {
+                let on = w.props.checked;
+                (field("checked"), quote!(bool), quote!(#on))
+            }
+            WidgetKind::Slider => {
+                let v = float(w.props.value, 3);
+                (field("value"), quote!(f32), v)
+            }
+            WidgetKind::ProgressBar => {
+                let v = float(w.props.value.clamp(0.0, 1.0), 3);
+                (field("progress"), quote!(f32), v)
+            }
+            WidgetKind::SelectableLabel => {
+                let on = w.props.checked;
+                (field("sel"), quote!(bool), quote!(#on))
+            }
+            WidgetKind::RadioGroup | WidgetKind::ComboBox | WidgetKind::MenuButton => {
+                let sel = if w.props.items.is_empty() {
+                    0
+                } else {
+                    w.props.selected.min(w.props.items.len() - 1)
+                };
+                let sel = int(sel);
+                (field("sel"), quote!(usize), quote!(#sel))
+            }
+            WidgetKind::CollapsingHeader => {
+                let open = w.props.checked;
+                (field("open"), quote!(bool), quote!(#open))
+            }
+            WidgetKind::DatePicker => {
+                let y = Literal::i32_unsuffixed(w.props.year);
+                let m = Literal::u32_unsuffixed(w.props.month.clamp(1, 12));
+                let d = Literal::u32_unsuffixed(w.props.day.clamp(1, 28));
+                (
+                    field("date"),
+                    quote!(chrono::NaiveDate),
+                    quote!(chrono::NaiveDate::from_ymd_opt(#y, #m, #d).unwrap()),
+                )
+            }
+            WidgetKind::Password => (field("pass"), quote!(String), quote!(#text.to_owned())),
+            WidgetKind::AngleSelector => {
+                let v = float(w.props.value, 3);
+                (field("angle"), quote!(f32), v)
+            }
+            WidgetKind::TextArea => (field("textarea"), quote!(String), quote!(#text.to_owned())),
+            WidgetKind::DragValue => {
+                let v = float(w.props.value, 3);
+                (field("drag"), quote!(f32), v)
+            }
+            WidgetKind::ColorPicker => {
+                let [r, g, b, a] = w.props.color.map(|c| int(c.into()));
+                (
+                    field("color"),
+                    quote!(egui::Color32),
+                    quote!(egui::Color32::from_rgba_unmultiplied(#r, #g, #b, #a)),
+                )
+            }
+            WidgetKind::Code => (field("code"), quote!(String), quote!(#text.to_owned())),
+            WidgetKind::TabBar => {
+                let tab = w.props.selected.min(w.props.items.len().saturating_sub(1));
+                let tab = int(tab);
+                (field("tab"), quote!(usize), quote!(#tab))
+            }
+            WidgetKind::Window => (
+                format_ident!("window_{}_open", w.id.as_u64()),
+                quote!(bool),
+                quote!(true),
+            ),
+            _ => continue,
+        };
+        fields.push((name, ty, init));
+    }
+    fields
+}
+
+/// The state struct `name`; `vis` applies to it and its fields
+pub(crate) fn state_struct(project: &Project, name: &Ident, vis: &TokenStream) -> TokenStream {
+    let fields = state_fields(project)
+        .into_iter()
+        .map(|(name, ty, _)| quote!(#vis #name: #ty));
+    quote! {
+        #vis struct #name {
+            #(#fields,)*
+        }
+    }
+}
+
+pub(crate) fn state_default(project: &Project, name: &Ident) -> TokenStream {
+    let fields = state_fields(project)
+        .into_iter()
+        .map(|(name, _, init)| quote!(#name: #init));
+    quote! {
+        impl Default for #name {
+            fn default() -> Self {
+                Self {
+                    #(#fields,)*
+                }
+            }
+        }
+    }
+}
+
+/// The accessible name and description set in the Inspector, if the kind's
+/// code can carry them
+fn access(w: &Widget) -> Option<(&str, &str)> {
+    let (label, description) = (&w.props.access_label, &w.props.access_description);
+    (w.kind.is_accessible() && !(label.is_empty() && description.is_empty()))
+        .then_some((label.as_str(), description.as_str()))
+}
+
+/// `expr;`, or `if expr.clicked() { click }` when there is something to run.
+/// `expr` is `w`'s response, passed through `gen_accessible` when needed.
+fn clickable(w: &Widget, expr: TokenStream, click: TokenStream) -> TokenStream {
+    let expr = match access(w) {
+        Some((label, description)) => quote!(gen_accessible(#expr, #label, #description)),
+        None => expr,
+    };
+    if click.is_empty() {
+        quote!(#expr;)
+    } else {
+        quote! {
+            if #expr.clicked() {
+                #click
+            }
+        }
+    }
+}
+
+/// `expr.field`, the response inside an `InnerResponse`, when `gen_accessible`
+/// needs it; otherwise `expr` as it is
+fn inner_response(w: &Widget, expr: TokenStream, field: TokenStream) -> TokenStream {
+    match access(w) {
+        Some(_) => quote!(#expr.#field),
+        None => expr,
+    }
+}
+
+/// Statements drawing a leaf widget into the current `ui`
+pub(crate) fn widget_body<G: CodeGenerator + ?Sized>(g: &G, w: &Widget) -> Vec<TokenStream> {
+    let size = w.size;
+    let (width, height) = (float(size.x, 1), float(size.y, 1));
+    // Stretched widgets fill whatever rect the anchors give them at runtime
+    let sized = if w.anchor.stretches() && w.is_anchorable() {
+        quote!(ui.available_size())
+    } else {
+        quote!(egui::vec2(#width, #height))
+    };
+    let text = &w.props.text;
+    let field = |prefix: &str| format_ident!("{prefix}_{}", w.id.as_u64());
+    let items = || {
+        if w.props.items.is_empty() {
+            quote!(vec!["Item".to_string()])
+        } else {
+            let items = &w.props.items;
+            quote!(vec![#(#items.to_string()),*])
+        }
+    };
+    let (min, max) = (float(w.props.min, 3), float(w.props.max, 3));
+    match w.kind {
+        WidgetKind::MenuButton => {
+            let (items, sel) = (items(), field("sel"));
+            let menu = quote! {
+                ui.menu_button(#text, |ui| {
+                    for (i, it) in items.iter().enumerate() {
+                        if ui.button(it).clicked() {
+                            state.#sel = i;
+                            ui.close_kind(egui::UiKind::Menu);
+                        }
+                    }
+                })
+            };
+            vec![
+                quote!(let items = #items;),
+                clickable(w, inner_response(w, menu, quote!(response)), quote!()),
+            ]
+        }
+        WidgetKind::Label => vec![clickable(w, quote!(ui.label(#text)), quote!())],
+        WidgetKind::Small => vec![clickable(w, quote!(ui.small(#text)), quote!())],
+        WidgetKind::Monospace => vec![clickable(w, quote!(ui.monospace(#text)), quote!())],
+        WidgetKind::Button => vec![clickable(
+            w,
+            quote!(ui.add_sized(#sized, egui::Button::new(#text))),
+            g.emit_click(w),
+        )],
+        WidgetKind::ImageTextButton => {
+            let icon = &w.props.icon;
+            vec![clickable(
+                w,
+                quote!(ui.add_sized(#sized, egui::Button::new(format!("{}  {}", #icon, #text)))),
+                g.emit_click(w),
+            )]
+        }
+        WidgetKind::Checkbox => {
+            let checked = field("checked");
+            vec![clickable(
+                w,
+                quote!(ui.checkbox(&mut state.#checked, #text)),
+                quote!(),
+            )]
+        }
+        WidgetKind::TextEdit => {
+            let value = field("text");
+            vec![clickable(
+                w,
+                quote!(ui.add_sized(#sized, egui::TextEdit::singleline(&mut state.#value).hint_text(#text))),
+                quote!(),
+            )]
+        }
+        WidgetKind::Slider => {
+            let value = field("value");
+            vec![clickable(
+                w,
+                quote!(ui.add_sized(#sized, egui::Slider::new(&mut state.#value, #min..=#max).text(#text))),
+                quote!(),
+            )]
+        }
+        WidgetKind::ProgressBar => {
+            let progress = field("progress");
+            vec![clickable(
+                w,
+                quote!(ui.add_sized(#sized, egui::ProgressBar::new(state.#progress).show_percentage())),
+                quote!(),
+            )]
+        }
+        WidgetKind::RadioGroup => {
+            let (items, sel) = (items(), field("sel"));
+            vec![
+                quote!(let items = #items;),
+                quote! {
+                    for (i, it) in items.iter().enumerate() {
+                        if ui.add(egui::RadioButton::new(state.#sel == i, it)).clicked() {
+                            state.#sel = i;
+                        }
+                    }
+                },
+            ]
+        }
+        WidgetKind::Link => vec![clickable(w, quote!(ui.link(#text)), g.emit_click(w))],
+        WidgetKind::Hyperlink => {
+            let url = &w.props.url;
+            vec![clickable(w, quote!(ui.hyperlink_to(#text, #url)), quote!())]
+        }
+        WidgetKind::SelectableLabel => {
+            let sel = field("sel");
+            vec![clickable(
+                w,
+                quote!(ui.add(egui::Button::selectable(state.#sel, #text))),
+                quote!(state.#sel = !state.#sel;),
+            )]
+        }
+        WidgetKind::ComboBox => {
+            let (items, sel) = (items(), field("sel"));
+            let salt = Literal::u64_unsuffixed(w.id.as_u64());
+            let combo = quote! {
+                egui::ComboBox::from_id_source(#salt)
+                    .width(#width)
+                    .selected_text(items.get(state.#sel).cloned().unwrap_or_else(|| "".to_string()))
+                    .show_ui(ui, |ui| {
+                        for (i, it) in items.iter().enumerate() {
+                            ui.selectable_value(&mut state.#sel, i, it.clone());
+                        }
+                    })
+            };
+            vec![
+                quote!(let items = #items;),
+                clickable(w, inner_response(w, combo, quote!(response)), quote!()),
+            ]
+        }
+        WidgetKind::Separator => vec![quote!(ui.separator();)],
+        WidgetKind::CollapsingHeader => {
+            let open = field("open");
+            let header = quote! {
+                egui::CollapsingHeader::new(#text)
+                    .default_open(state.#open)
+                    .show(ui, |ui| {
+                        ui.label("… place your inner content here …");
+                    })
+            };
+            vec![clickable(
+                w,
+                inner_response(w, header, quote!(header_response)),
+                quote!(),
+            )]
+        }
+        WidgetKind::DatePicker => {
+            let date = field("date");
+            let picker = clickable(
+                w,
+                quote!(ui.add(egui_extras::DatePickerButton::new(&mut state.#date))),
+                quote!(),
+            );
+            vec![quote! {
+                ui.horizontal(|ui| {
+                    ui.label(#text);
+                    #picker
+                });
+            }]
+        }
+        WidgetKind::Password => {
+            let pass = field("pass");
+            vec![clickable(
+                w,
+                quote!(ui.add_sized(#sized, egui::TextEdit::singleline(&mut state.#pass).password(true).hint_text("password"))),
+                quote!(),
+            )]
+        }
+        WidgetKind::AngleSelector => {
+            let angle = field("angle");
+            vec![clickable(
+                w,
+                quote!(ui.add_sized(#sized, egui::Slider::new(&mut state.#angle, #min..=#max).suffix("°").text(#text))),
+                quote!(),
+            )]
+        }
+        WidgetKind::Tree => {
+            // Helpers live only in the generator (not emitted), so we can use any Rust we want here:
+            #[derive(Clone)]
+            struct Node {
+                label: String,
+                children: Vec<Node>,
+            }
+
+            fn parse_nodes(lines: &[String]) -> Vec<Node> {
+                let items: Vec<(usize, String)> = lines
+                    .iter()
+                    .map(|s| {
+                        let indent = s.chars().take_while(|c| *c == ' ').count() / 2;
+                        (indent, s.trim().to_string())
+                    })
+                    .filter(|(_, s)| !s.is_empty())
+                    .collect();
+
+                fn build<I: Iterator<Item = (usize, String)>>(
+                    it: &mut std::iter::Peekable<I>,
+                    level: usize,
+                ) -> Vec<Node> {
+                    let mut out = Vec::new();
+                    while let Some((ind, _)) = it.peek().cloned() {
+                        if ind < level {
+                            break;
+                        }
+                        if ind > level {
+                            break;
+                        }
+                        let (_, label) = it.next().unwrap();
+                        let children = build(it, level + 1);
+                        out.push(Node { label, children });
+                    }
+                    out
+                }
+
+                let mut it = items.into_iter().peekable();
+                build(&mut it, 0)
+            }
+
+            fn nodes_literal(nodes: &[Node]) -> TokenStream {
+                let nodes = nodes.iter().map(|n| {
+                    let label = &n.label;
+                    let children = nodes_literal(&n.children);
+                    quote!(GenTreeNode { label: #label.to_string(), children: #children })
+                });
+                quote!(vec![#(#nodes),*])
+            }
+
+            let items = if w.props.items.is_empty() {
+                vec!["Root".into(), "  Child".into()]
+            } else {
+                w.props.items.clone()
+            };
+
+            let nodes = nodes_literal(&parse_nodes(&items));
+            vec![
+                quote!(let nodes: Vec<GenTreeNode> = #nodes;),
+                quote! {
+                    egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                        gen_show_tree(ui, &nodes);
+                    });
+                },
+            ]
+        }
+        WidgetKind::TextArea => {
+            let value = field("textarea");
+            vec![clickable(
+                w,
+                quote!(ui.add_sized(#sized, egui::TextEdit::multiline(&mut state.#value).desired_rows(5))),
+                quote!(),
+            )]
+        }
+        WidgetKind::DragValue => {
+            let drag = field("drag");
+            let drag = clickable(
+                w,
+                quote!(ui.add(egui::DragValue::new(&mut state.#drag).range(#min..=#max))),
+                quote!(),
+            );
+            vec![quote! {
+                ui.horizontal(|ui| {
+                    ui.label(#text);
+                    #drag
+                });
+            }]
+        }
+        WidgetKind::Spinner => vec![clickable(w, quote!(ui.add(egui::Spinner::new())), quote!())],
+        WidgetKind::ColorPicker => {
+            let color = field("color");
+            let picker = clickable(
+                w,
+                quote!(egui::color_picker::color_edit_button_srgba(ui, &mut state.#color, egui::color_picker::Alpha::OnlyBlend)),
+                quote!(),
+            );
+            vec![quote! {
+                ui.horizontal(|ui| {
+                    ui.label(#text);
+                    #picker
+                });
+            }]
+        }
+        WidgetKind::Code => {
+            let code = field("code");
+            let editor = clickable(
+                w,
+                quote!(ui.add(egui::TextEdit::multiline(&mut state.#code).code_editor().desired_width(#width).desired_rows(8))),
+                quote!(),
+            );
+            vec![quote! {
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    #editor
+                });
+            }]
+        }
+        WidgetKind::Heading => vec![clickable(w, quote!(ui.heading(#text)), quote!())],
+        WidgetKind::Image => {
+            let source = match g.context().project.widget_image(w) {
+                Some(asset) => {
+                    let path = format!("../{}", asset.export_path());
+                    quote!(egui::include_image!(#path))
+                }
+                None => {
+                    let url = &w.props.url;
+                    quote!(#url)
+                }
+            };
+            vec![clickable(
+                w,
+                quote!(ui.add(egui::Image::new(#source).fit_to_exact_size(egui::vec2(#width, #height)))),
+                quote!(),
+            )]
+        }
+        WidgetKind::Placeholder => {
+            let [r, g, b, a] = w.props.color.map(|c| int(c.into()));
+            vec![quote! {
+                egui::Frame::NONE
+                    .fill(egui::Color32::from_rgba_unmultiplied(#r, #g, #b, #a))
+                    .corner_radius(4.0)
+                    .show(ui, |ui| {
+                        ui.set_min_size(egui::vec2(#width, #height));
+                        ui.centered_and_justified(|ui| ui.label(#text));
+                    });
+            }]
+        }
+        WidgetKind::Group => {
+            let title = if text.is_empty() {
+                quote!()
+            } else {
+                quote!(ui.strong(#text); ui.separator();)
+            };
+            let layout_fn = if w.props.horizontal {
+                format_ident!("horizontal")
+            } else {
+                format_ident!("vertical")
+            };
+            let (iw, ih) = (float(size.x - 12.0, 1), float(size.y - 12.0, 1));
+            let contents = comment("group contents");
+            vec![quote! {
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.set_min_size(egui::vec2(#iw, #ih));
+                    ui.#layout_fn(|ui| { #title #contents });
+                });
+            }]
+        }
+        WidgetKind::ScrollBox => {
+            let (sw, sh) = (float(size.x - 4.0, 1), float(size.y - 4.0, 1));
+            vec![quote! {
+                egui::ScrollArea::both()
+                    .max_width(#sw)
+                    .max_height(#sh)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        ui.label(#text);
+                    });
+            }]
+        }
+        WidgetKind::Columns => {
+            let cols = int(w.props.columns.max(1));
+            vec![quote! {
+                ui.columns(#cols, |columns| {
+                    for col in columns.iter_mut() {
+                        col.label(#text);
+                    }
+                });
+            }]
+        }
+        // Drawn with their children by `container_body`
+        WidgetKind::Grid | WidgetKind::TabBar => Vec::new(),
+        // Windows float on their own; see `absolute_code`
+        WidgetKind::Window => vec![comment("window contents")],
+    }
+}
+
+/// Statements drawing a container and the widgets slotted into it
+pub(crate) fn container_body<G: CodeGenerator + ?Sized>(g: &G, w: &Widget) -> Vec<TokenStream> {
+    let project = g.context().project;
+    let field = |prefix: &str| format_ident!("{prefix}_{}", w.id.as_u64());
+    match w.kind {
+        WidgetKind::TabBar => {
+            let tab_field = field("tab");
+            let tabs = w.props.items.iter().enumerate().map(|(i, tab)| {
+                let i = int(i);
+                quote!(ui.selectable_value(&mut state.#tab_field, #i, #tab);)
+            });
+            // children are placed relative to the top-left of the tab content
+            let content = w.tab_content().min.to_vec2();
+            let mut arms = Vec::new();
+            let mut uses_origin = false;
+            for tab in 0..w.props.items.len() {
+                let children: Vec<Widget> = project
+                    .widgets
+                    .iter()
+                    .filter(|c| c.slot.is_some_and(|s| s.parent == w.id && s.row == tab))
+                    .map(|c| Widget {
+                        pos: c.pos - content,
+                        anchor: Anchor::default(),
+                        ..c.clone()
+                    })
+                    .collect();
+                let children: Vec<&Widget> = children.iter().collect();
+                uses_origin |= places_absolutely(g, &children);
+                let body = placed_code(g, &children, &quote!(tab_origin));
+                let tab = int(tab);
+                arms.push(quote!(#tab => { #body }));
+            }
+            let mut body = vec![
+                quote!(ui.horizontal(|ui| { #(#tabs)* });),
+                quote!(ui.separator();),
+            ];
+            if uses_origin {
+                body.push(quote!(let tab_origin = ui.cursor().min;));
+            }
+            body.push(quote! {
+                match state.#tab_field {
+                    #(#arms)*
+                    _ => {}
+                }
+            });
+            body
+        }
+        WidgetKind::Grid => {
+            let mut body = Vec::new();
+            for row in 0..w.props.rows.max(1) {
+                for col in 0..w.props.columns.max(1) {
+                    let slot = Some(Slot {
+                        parent: w.id,
+                        row,
+                        col,
+                    });
+                    let children: Vec<&Widget> =
+                        project.widgets.iter().filter(|c| c.slot == slot).collect();
+                    body.push(cell_body(g, &children));
+                }
+                body.push(quote!(ui.end_row();));
+            }
+            let id = format!("grid_{}", w.id);
+            let cols = int(w.props.columns.max(1));
+            let striped = w.props.striped;
+            let (sx, sy) = (float(w.props.spacing[0], 1), float(w.props.spacing[1], 1));
+            let min_col_width = float(w.props.min_col_width, 1);
+            vec![quote! {
+                egui::Grid::new(#id)
+                    .num_columns(#cols)
+                    .striped(#striped)
+                    .spacing(egui::vec2(#sx, #sy))
+                    .min_col_width(#min_col_width)
+                    .show(ui, |ui| { #(#body)* });
+            }]
+        }
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,92 @@
+//! Line diffs between an earlier copy of the generated code and the
+//! current output, to review a design tweak before pasting it over code
+//! that's already in a repository.
+
+use similar::TextDiff;
+
+pub(crate) use similar::ChangeTag;
+
+/// Lines of context kept around each change
+const CONTEXT: usize = 3;
+
+/// A run of changes with the unchanged lines around it
+pub(crate) struct Hunk {
+    /// `@@ -1,4 +1,5 @@`
+    pub(crate) header: String,
+    /// Lines without their line ending
+    pub(crate) lines: Vec<(ChangeTag, String)>,
+}
+
+pub(crate) struct Diff {
+    pub(crate) hunks: Vec<Hunk>,
+    pub(crate) added: usize,
+    pub(crate) removed: usize,
+    /// The whole diff in unified format, for `git apply` or `patch`
+    pub(crate) patch: String,
+}
+
+impl Diff {
+    /// `old_name` and `new_name` label the two sides in the patch header
+    pub(crate) fn new(old: &str, new: &str, old_name: &str, new_name: &str) -> Self {
+        let text = TextDiff::from_lines(old, new);
+        let mut unified = text.unified_diff();
+        unified.context_radius(CONTEXT).header(old_name, new_name);
+        let hunks: Vec<Hunk> = unified
+            .iter_hunks()
+            .map(|hunk| Hunk {
+                header: hunk.header().to_string(),
+                lines: hunk
+                    .iter_changes()
+                    .map(|change| {
+                        let line = change.value().trim_end_matches(['\n', '\r']);
+                        (change.tag(), line.to_owned())
+                    })
+                    .collect(),
+            })
+            .collect();
+        let count = |tag| {
+            hunks
+                .iter()
+                .flat_map(|h| &h.lines)
+                .filter(|(t, _)| *t == tag)
+                .count()
+        };
+        Self {
+            added: count(ChangeTag::Insert),
+            removed: count(ChangeTag::Delete),
+            patch: unified.to_string(),
+            hunks,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_and_patch() {
+        let old = "fn ui() {\n    ui.label(\"Hi\");\n    ui.separator();\n}\n";
+        let new =
+            "fn ui() {\n    ui.label(\"Hello\");\n    ui.separator();\n    ui.spinner();\n}\n";
+        let diff = Diff::new(old, new, "a/src/main.rs", "b/src/main.rs");
+        assert_eq!((diff.added, diff.removed), (2, 1));
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].header, "@@ -1,4 +1,5 @@");
+        assert_eq!(
+            diff.hunks[0].lines[1],
+            (ChangeTag::Delete, "    ui.label(\"Hi\");".to_owned())
+        );
+        assert!(
+            diff.patch
+                .starts_with("--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,4 +1,5 @@\n")
+        );
+        assert!(diff.patch.contains("\n+    ui.spinner();\n"));
+
+        assert!(Diff::new(new, new, "a", "b").is_empty());
+    }
+}
@@ -1,16 +1,156 @@
-//! Syntax highlighting for generated code using syntect.
+//! Syntax highlighting for generated code using syntect. The code panel
+//! shows Rust, Export JSON's project file, and Separate Files' Cargo.toml
+//! and index.html, so the language is picked per section.
 
 use egui::Color32;
+use egui::text::LayoutJob;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{Style, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use syntect::highlighting::{HighlightState, Style, Theme, ThemeSet};
+use syntect::parsing::{
+    ParseState, SyntaxDefinition, SyntaxReference, SyntaxSet, SyntaxSetBuilder,
+};
 use syntect::util::LinesWithEndings;
 
+/// Font of highlighted code, in the editor and its line numbers
+pub const CODE_FONT_SIZE: f32 = 12.0;
+
+/// Padding around the text inside the code editor
+const EDITOR_MARGIN: egui::Margin = egui::Margin::symmetric(4, 2);
+
+/// syntect has no TOML syntax; this covers what Cargo.toml uses
+const TOML_SYNTAX: &str = r#"%YAML 1.2
+---
+name: TOML
+file_extensions: [toml]
+scope: source.toml
+contexts:
+  main:
+    - match: '#.*$'
+      scope: comment.line.number-sign.toml
+    - match: '^\s*(\[\[?)([^\]]*)(\]\]?)'
+      captures:
+        1: punctuation.definition.table.toml
+        2: entity.name.section.toml
+        3: punctuation.definition.table.toml
+    - match: '([A-Za-z0-9_.-]+|"[^"]*")\s*(=)'
+      captures:
+        1: entity.name.tag.toml
+        2: keyword.operator.assignment.toml
+    - match: '"""'
+      push: triple_string
+    - match: '"'
+      push: string
+    - match: "'[^']*'"
+      scope: string.quoted.single.toml
+    - match: '\b(true|false)\b'
+      scope: constant.language.boolean.toml
+    - match: '[+-]?\b[0-9][0-9_]*(\.[0-9_]+)?([eE][+-]?[0-9]+)?\b'
+      scope: constant.numeric.toml
+  string:
+    - meta_scope: string.quoted.double.toml
+    - match: '\\.'
+      scope: constant.character.escape.toml
+    - match: '"|$'
+      pop: true
+  triple_string:
+    - meta_scope: string.quoted.triple.toml
+    - match: '"""'
+      pop: true
+"#;
+
+/// A language the code panel can show
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Json,
+    Toml,
+    Html,
+}
+
+impl Language {
+    fn extension(self) -> &'static str {
+        match self {
+            Language::Rust => "rs",
+            Language::Json => "json",
+            Language::Toml => "toml",
+            Language::Html => "html",
+        }
+    }
+
+    /// The language of a file, by its name
+    pub fn for_path(path: &str) -> Self {
+        match path.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("json") => Language::Json,
+            Some("toml") => Language::Toml,
+            Some("html" | "htm") => Language::Html,
+            _ => Language::Rust,
+        }
+    }
+
+    /// The language of each line. Text starting like a JSON document or a
+    /// TOML table is that; otherwise it's Rust, and the `// FILE: name`
+    /// banners of Separate Files switch language for the section after them.
+    pub fn of_lines(lines: &[&str]) -> Vec<Self> {
+        let first = lines.iter().map(|l| l.trim()).find(|l| !l.is_empty());
+        let is_table = |l: &str| {
+            l.strip_prefix('[')
+                .and_then(|l| l.strip_suffix(']'))
+                .is_some_and(|name| {
+                    name.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "._-[]".contains(c))
+                })
+        };
+        let mut current = match first {
+            Some(l) if is_table(l) => Language::Toml,
+            Some(l) if l.starts_with('{') || l.starts_with('[') => Language::Json,
+            _ => Language::Rust,
+        };
+        lines
+            .iter()
+            .map(|line| {
+                let line = line.trim();
+                if let Some(path) = line.strip_prefix("// FILE: ") {
+                    current = Language::for_path(path);
+                    Language::Rust
+                } else if line.starts_with("// ===") {
+                    // banner rules are Rust comments whatever section they close
+                    Language::Rust
+                } else {
+                    current
+                }
+            })
+            .collect()
+    }
+}
+
 /// Cached syntax highlighting resources.
 pub struct Highlighter {
     syntax_set: SyntaxSet,
+    /// Just `TOML_SYNTAX`; linking it into `syntax_set` would mean
+    /// rebuilding all of the defaults
+    toml_set: SyntaxSet,
     theme_set: ThemeSet,
     theme_name: String,
+    /// The last job built, keyed by a hash of its code
+    cached: Option<(u64, LayoutJob)>,
+    /// Lines of the last code highlighted, so an edit only re-highlights
+    /// from the changed line until the highlighter is back in step
+    lines: Vec<Line>,
+}
+
+/// Where a highlighter is within the text, so it can resume from a line
+type State = (HighlightState, ParseState);
+
+/// A highlighted line and the highlighter state around it
+#[derive(Clone)]
+struct Line {
+    text: String,
+    language: Language,
+    spans: Vec<(Range<usize>, Color32)>,
+    before: State,
+    after: State,
 }
 
 impl Default for Highlighter {
@@ -21,22 +161,75 @@ impl Default for Highlighter {
 
 impl Highlighter {
     pub fn new() -> Self {
+        let mut toml = SyntaxSetBuilder::new();
+        toml.add(
+            SyntaxDefinition::load_from_str(TOML_SYNTAX, true, None)
+                .expect("built-in TOML syntax is valid"),
+        );
         Self {
             syntax_set: SyntaxSet::load_defaults_newlines(),
+            toml_set: toml.build(),
             theme_set: ThemeSet::load_defaults(),
-            theme_name: "base16-ocean.dark".to_string(),
+            theme_name: Self::default_theme(true).to_string(),
+            cached: None,
+            lines: Vec::new(),
         }
     }
 
-    /// Highlight Rust code and return a list of (text, color) spans.
-    pub fn highlight_rust(&self, code: &str) -> Vec<(String, Color32)> {
-        let syntax = self
-            .syntax_set
-            .find_syntax_by_extension("rs")
-            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+    /// The theme used until the user picks one
+    pub fn default_theme(dark: bool) -> &'static str {
+        if dark {
+            "base16-ocean.dark"
+        } else {
+            "base16-ocean.light"
+        }
+    }
 
-        let theme = self
+    pub fn theme_name(&self) -> &str {
+        &self.theme_name
+    }
+
+    /// Names of the themes with a dark (or light) background, sorted
+    pub fn themes(&self, dark: bool) -> Vec<&str> {
+        let mut names: Vec<&str> = self
             .theme_set
+            .themes
+            .iter()
+            .filter(|(_, theme)| {
+                let is_dark = theme.settings.background.is_none_or(|c| {
+                    0.299 * f32::from(c.r) + 0.587 * f32::from(c.g) + 0.114 * f32::from(c.b) < 128.0
+                });
+                is_dark == dark
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Switch theme; unknown names are ignored
+    pub fn set_theme(&mut self, name: &str) {
+        if name != self.theme_name && self.theme_set.themes.contains_key(name) {
+            self.theme_name = name.to_owned();
+            self.cached = None;
+            self.lines.clear();
+        }
+    }
+
+    /// The syntax for `language` and the set it belongs to
+    fn syntax(&self, language: Language) -> (&SyntaxSet, &SyntaxReference) {
+        let set = match language {
+            Language::Toml => &self.toml_set,
+            _ => &self.syntax_set,
+        };
+        let syntax = set
+            .find_syntax_by_extension(language.extension())
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+        (set, syntax)
+    }
+
+    fn theme(&self) -> &Theme {
+        self.theme_set
             .themes
             .get(&self.theme_name)
             .unwrap_or_else(|| {
@@ -45,47 +238,115 @@ impl Highlighter {
                     .values()
                     .next()
                     .expect("No themes available")
-            });
-
-        let mut highlighter = HighlightLines::new(syntax, theme);
-        let mut result = Vec::new();
-
-        for line in LinesWithEndings::from(code) {
-            match highlighter.highlight_line(line, &self.syntax_set) {
-                Ok(ranges) => {
-                    for (style, text) in ranges {
-                        let color = style_to_color32(style);
-                        result.push((text.to_string(), color));
-                    }
-                }
-                Err(_) => {
-                    // Fallback to plain text on error
-                    result.push((line.to_string(), Color32::LIGHT_GRAY));
-                }
-            }
-        }
-
-        result
+            })
     }
 
-    /// Render highlighted code as a LayoutJob for egui.
-    pub fn layout_job(&self, code: &str) -> egui::text::LayoutJob {
-        let mut job = egui::text::LayoutJob::default();
+    /// Highlight code and return a list of (text, color) spans.
+    pub fn highlight(&mut self, code: &str) -> Vec<(String, Color32)> {
+        self.highlight_lines(code);
+        self.lines
+            .iter()
+            .flat_map(|line| {
+                line.spans
+                    .iter()
+                    .map(|(range, color)| (line.text[range.clone()].to_owned(), *color))
+            })
+            .collect()
+    }
 
-        for (text, color) in self.highlight_rust(code) {
+    /// Render highlighted code as a LayoutJob for egui. Highlighting is
+    /// redone only when the code differs from the last call's, and then
+    /// only for the lines an edit affected.
+    pub fn layout_job(&mut self, code: &str) -> LayoutJob {
+        let mut hasher = DefaultHasher::new();
+        code.hash(&mut hasher);
+        let key = hasher.finish();
+        if let Some((cached, job)) = &self.cached
+            && *cached == key
+        {
+            return job.clone();
+        }
+        let mut job = LayoutJob::default();
+        for (text, color) in self.highlight(code) {
             job.append(
                 &text,
                 0.0,
                 egui::TextFormat {
-                    font_id: egui::FontId::monospace(12.0),
+                    font_id: egui::FontId::monospace(CODE_FONT_SIZE),
                     color,
                     ..Default::default()
                 },
             );
         }
-
+        self.cached = Some((key, job.clone()));
         job
     }
+
+    /// Bring `lines` up to date with `code`. Lines before the first change
+    /// are kept; after it, highlighting resumes until it reaches the
+    /// unchanged tail in the same state it was in before.
+    fn highlight_lines(&mut self, code: &str) {
+        let new: Vec<&str> = LinesWithEndings::from(code).collect();
+        let languages = Language::of_lines(&new);
+        let old = std::mem::take(&mut self.lines);
+        let unchanged =
+            |(o, (n, l)): (&Line, (&&str, &Language))| o.text == **n && o.language == *l;
+        let prefix = old
+            .iter()
+            .zip(new.iter().zip(&languages))
+            .take_while(|&pair| unchanged(pair))
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().zip(&languages[prefix..]).rev())
+            .take_while(|&pair| unchanged(pair))
+            .count();
+        let (tail_old, tail_new) = (old.len() - suffix, new.len() - suffix);
+
+        let mut lines = old[..prefix].to_vec();
+        let mut state = lines.last().map(|l: &Line| (l.language, l.after.clone()));
+        for (i, (text, &language)) in new.iter().zip(&languages).enumerate().skip(prefix) {
+            // each section starts its language afresh
+            let before = match state {
+                Some((previous, after)) if previous == language => after,
+                _ => HighlightLines::new(self.syntax(language).1, self.theme()).state(),
+            };
+            if i >= tail_new {
+                let old_i = tail_old + (i - tail_new);
+                if old[old_i].before == before {
+                    lines.extend_from_slice(&old[old_i..]);
+                    break;
+                }
+            }
+            let mut highlighter =
+                HighlightLines::from_state(self.theme(), before.0.clone(), before.1.clone());
+            let spans = match highlighter.highlight_line(text, self.syntax(language).0) {
+                Ok(ranges) => {
+                    let mut start = 0;
+                    ranges
+                        .into_iter()
+                        .map(|(style, piece)| {
+                            start += piece.len();
+                            (start - piece.len()..start, style_to_color32(style))
+                        })
+                        .collect()
+                }
+                // Fallback to plain text on error
+                Err(_) => vec![(0..text.len(), Color32::LIGHT_GRAY)],
+            };
+            let after = highlighter.state();
+            state = Some((language, after.clone()));
+            lines.push(Line {
+                text: (*text).to_owned(),
+                language,
+                spans,
+                before,
+                after,
+            });
+        }
+        self.lines = lines;
+    }
 }
 
 /// Convert syntect Style to egui Color32.
@@ -93,49 +354,76 @@ fn style_to_color32(style: Style) -> Color32 {
     Color32::from_rgb(style.foreground.r, style.foreground.g, style.foreground.b)
 }
 
-/// Simple code viewer with syntax highlighting (read-only).
-#[allow(dead_code)]
-pub fn code_viewer(ui: &mut egui::Ui, highlighter: &Highlighter, code: &str) {
-    let job = highlighter.layout_job(code);
-
-    egui::ScrollArea::vertical()
-        .id_salt("highlighted_code_scroll")
-        .max_height(280.0)
-        .auto_shrink([false, false])
-        .show(ui, |ui| {
-            // Use a Label with the layout job for syntax-highlighted display
-            ui.add(egui::Label::new(job).selectable(true));
-        });
+/// Gives `range` of the job's text a background, splitting the sections
+/// it starts or ends inside
+fn tint(job: &mut LayoutJob, range: Range<usize>, fill: Color32) {
+    let mut sections = Vec::with_capacity(job.sections.len() + 2);
+    for section in job.sections.drain(..) {
+        let bytes = section.byte_range.clone();
+        let cuts = [bytes.start, range.start, range.end, bytes.end];
+        let mut cuts: Vec<usize> = cuts
+            .into_iter()
+            .filter(|c| bytes.contains(c) || *c == bytes.end)
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+        let mut start = bytes.start;
+        for end in cuts.into_iter().filter(|&c| c > bytes.start) {
+            let mut part = section.clone();
+            if start != bytes.start {
+                part.leading_space = 0.0;
+            }
+            part.byte_range = start..end;
+            if range.contains(&start) {
+                part.format.background = fill;
+            }
+            sections.push(part);
+            start = end;
+        }
+    }
+    job.sections = sections;
 }
 
-/// Code editor with syntax highlighting (editable).
-/// Returns true if the code was modified.
-#[allow(dead_code)]
+/// Code editor with syntax highlighting and line numbers, for use inside
+/// a scroll area. Lines don't wrap, so the numbers stay beside their line.
+/// `tints` give byte ranges a background, e.g. the selected widget's code
+/// or search matches; later ones paint over earlier ones.
 pub fn code_editor_highlighted(
     ui: &mut egui::Ui,
-    _highlighter: &Highlighter,
+    highlighter: &mut Highlighter,
     code: &mut String,
-) -> bool {
-    let mut changed = false;
-
-    egui::ScrollArea::vertical()
-        .id_salt("code_editor_scroll")
-        .max_height(280.0)
-        .auto_shrink([false, false])
-        .show(ui, |ui| {
-            // For editing, we use a regular TextEdit with code_editor styling
-            // Syntax highlighting on edit is expensive, so we show it read-only
-            // The user can toggle between edit and view modes
-            let response = ui.add(
-                egui::TextEdit::multiline(code)
-                    .code_editor()
-                    .desired_rows(18)
-                    .desired_width(f32::INFINITY),
-            );
-            changed = response.changed();
+    tints: &[(Range<usize>, Color32)],
+) -> egui::text_edit::TextEditOutput {
+    let font = egui::FontId::monospace(CODE_FONT_SIZE);
+    let numbers: Vec<String> = (1..=code.split('\n').count())
+        .map(|n| n.to_string())
+        .collect();
+    ui.horizontal_top(|ui| {
+        ui.vertical(|ui| {
+            ui.add_space(f32::from(EDITOR_MARGIN.top));
+            let numbers = egui::RichText::new(numbers.join("\n"))
+                .font(font.clone())
+                .weak();
+            ui.add(egui::Label::new(numbers).selectable(false));
         });
-
-    changed
+        let mut layouter = |ui: &egui::Ui, text: &dyn egui::TextBuffer, _wrap_width: f32| {
+            let mut job = highlighter.layout_job(text.as_str());
+            for (range, fill) in tints {
+                tint(&mut job, range.clone(), *fill);
+            }
+            ui.fonts_mut(|f| f.layout_job(job))
+        };
+        egui::TextEdit::multiline(code)
+            .code_editor()
+            .font(font)
+            .margin(EDITOR_MARGIN)
+            .lock_focus(true)
+            .desired_rows(18)
+            .desired_width(f32::INFINITY)
+            .layouter(&mut layouter)
+            .show(ui)
+    })
+    .inner
 }
 
 #[cfg(test)]
@@ -144,22 +432,111 @@ mod tests {
 
     #[test]
     fn test_highlighter_creation() {
-        let highlighter = Highlighter::new();
+        let mut highlighter = Highlighter::new();
         // Should not panic
-        let _ = highlighter.highlight_rust("fn main() {}");
+        let _ = highlighter.highlight("fn main() {}");
     }
 
     #[test]
     fn test_highlight_rust_basic() {
-        let highlighter = Highlighter::new();
-        let spans = highlighter.highlight_rust("fn main() {\n    println!(\"Hello\");\n}\n");
+        let mut highlighter = Highlighter::new();
+        let spans = highlighter.highlight("fn main() {\n    println!(\"Hello\");\n}\n");
         assert!(!spans.is_empty());
     }
 
     #[test]
     fn test_layout_job() {
-        let highlighter = Highlighter::new();
+        let mut highlighter = Highlighter::new();
         let job = highlighter.layout_job("let x = 42;");
         assert!(!job.text.is_empty());
+        assert_eq!(highlighter.layout_job("let x = 42;").text, job.text);
+        assert_eq!(highlighter.layout_job("let y = 1;").text, "let y = 1;");
+    }
+
+    #[test]
+    fn test_tint_splits_sections() {
+        let mut highlighter = Highlighter::new();
+        let mut job = highlighter.layout_job("let value = 42;");
+        let fill = Color32::YELLOW;
+        tint(&mut job, 5..8, fill);
+        let tinted: Vec<_> = job
+            .sections
+            .iter()
+            .filter(|s| s.format.background == fill)
+            .map(|s| &job.text[s.byte_range.clone()])
+            .collect();
+        assert_eq!(tinted.concat(), "alu");
+        assert!(
+            job.sections
+                .windows(2)
+                .all(|w| w[0].byte_range.end == w[1].byte_range.start)
+        );
+    }
+
+    #[test]
+    fn test_incremental_highlighting_matches_fresh() {
+        let code = "fn a() {\n    let s = \"x\";\n}\n\nfn b() {}\n";
+        let edits = [
+            code.replace("\"x\"", "\"y\""),
+            // opening a comment recolors every line after it
+            code.replace("fn a()", "/* fn a()"),
+            code.replace("/* fn a()", "fn a()"),
+            format!("// top\n{code}"),
+            code.replace("\n\n", "\n"),
+            String::new(),
+        ];
+        let mut incremental = Highlighter::new();
+        incremental.layout_job(code);
+        for edit in &edits {
+            let job = incremental.layout_job(edit);
+            assert_eq!(job, Highlighter::new().layout_job(edit), "{edit:?}");
+        }
+    }
+
+    #[test]
+    fn test_languages_per_section() {
+        use Language::*;
+        let lines = [
+            "// ====\n",
+            "// FILE: Cargo.toml\n",
+            "// ====\n",
+            "[package]\n",
+            "name = \"x\"\n",
+            "\n",
+            "// ====\n",
+            "// FILE: src/main.rs\n",
+            "// ====\n",
+            "fn main() {}\n",
+        ];
+        assert_eq!(
+            Language::of_lines(&lines),
+            [Rust, Rust, Rust, Toml, Toml, Toml, Rust, Rust, Rust, Rust]
+        );
+        assert_eq!(Language::of_lines(&["  {\n", "}\n"]), [Json, Json]);
+        assert_eq!(Language::of_lines(&["[dependencies]\n"]), [Toml]);
+        assert_eq!(Language::of_lines(&["#[derive(Default)]\n"]), [Rust]);
+
+        // the TOML syntax loads and colors keys differently from values
+        let mut highlighter = Highlighter::new();
+        let spans = highlighter.highlight("[package]\nname = \"x\"\n");
+        let color = |text: &str| spans.iter().find(|(t, _)| t == text).map(|(_, c)| *c);
+        assert_ne!(color("name"), color("\"x\"").or(color("\"")));
+    }
+
+    #[test]
+    fn test_themes_by_mode() {
+        let mut highlighter = Highlighter::new();
+        let dark = highlighter.themes(true);
+        let light = highlighter.themes(false);
+        assert!(dark.contains(&Highlighter::default_theme(true)));
+        assert!(light.contains(&Highlighter::default_theme(false)));
+        assert!(dark.iter().all(|t| !light.contains(t)));
+        let pick = light[0].to_owned();
+        let before = highlighter.layout_job("fn main() {}");
+        highlighter.set_theme(&pick);
+        assert_eq!(highlighter.theme_name(), pick);
+        assert_ne!(highlighter.layout_job("fn main() {}"), before);
+        highlighter.set_theme("no such theme");
+        assert_eq!(highlighter.theme_name(), pick);
     }
 }
@@ -0,0 +1,1329 @@
+//! Rebuilds a `Project` from Rust this tool generated, for when the project
+//! file is lost but the code survived. Absolute layouts come back exactly;
+//! flow layouts are re-placed the way egui would lay them out.
+
+use crate::{
+    layout::ITEM_SPACING,
+    project::Project,
+    widget::{Anchor, DockArea, HAnchor, Slot, VAnchor, Widget, WidgetId, WidgetKind},
+};
+use egui::{Pos2, Rect, Vec2, pos2, vec2};
+use quote::ToTokens;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use syn::{
+    Expr, ExprMethodCall, Stmt,
+    spanned::Spanned,
+    visit::{self, Visit},
+};
+
+/// Where the Separate Files format starts the Rust source
+const MAIN_MARKER: &str = "// FILE: src/main.rs\n";
+
+/// A project rebuilt from generated code, and what couldn't be mapped back
+pub(crate) struct Imported {
+    pub(crate) project: Project,
+    pub(crate) warnings: Vec<String>,
+}
+
+/// Parse the output of any code generation format back into a project.
+/// Statements the generator doesn't emit are skipped with a warning.
+pub(crate) fn import_code(src: &str) -> Result<Imported, String> {
+    let (src, line_offset) = match src.find(MAIN_MARKER) {
+        Some(i) => (
+            &src[i + MAIN_MARKER.len()..],
+            src[..i + MAIN_MARKER.len()].matches('\n').count(),
+        ),
+        None => (src, 0),
+    };
+    let file = syn::parse_file(src)
+        .map_err(|e| format!("line {}: {e}", e.span().start().line + line_offset))?;
+    let body =
+        ui_body(&file).ok_or("no `generated_ui` function or component `ui` method to import")?;
+    let mut importer = Importer {
+        defaults: defaults(&file),
+        design: designed_size(src).unwrap_or(Project::default().canvas_size),
+        widgets: Vec::new(),
+        warnings: Vec::new(),
+        line_offset,
+    };
+    importer.panels(body);
+    Ok(importer.finish())
+}
+
+/// The statements drawing the panels: `generated_ui`'s body, or the
+/// `ui.scope` closure of a component's `ui` method.
+fn ui_body(file: &syn::File) -> Option<&[Stmt]> {
+    for item in &file.items {
+        match item {
+            syn::Item::Fn(f) if f.sig.ident == "generated_ui" => return Some(&f.block.stmts),
+            syn::Item::Impl(i) if i.trait_.is_none() => {
+                for item in &i.items {
+                    let syn::ImplItem::Fn(f) = item else {
+                        continue;
+                    };
+                    if f.sig.ident != "ui" {
+                        continue;
+                    }
+                    let scope = f.block.stmts.iter().find_map(|s| match s {
+                        Stmt::Local(l) => match l.init.as_ref().map(|i| &*i.expr) {
+                            Some(Expr::Field(f)) => method(&f.base, "scope"),
+                            _ => None,
+                        },
+                        _ => None,
+                    });
+                    if let Some(body) = scope.and_then(|m| m.args.first()).and_then(closure_body) {
+                        return Some(body);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Field initialisers of every `impl Default`, by field name
+fn defaults(file: &syn::File) -> HashMap<String, Expr> {
+    let mut out = HashMap::new();
+    for item in &file.items {
+        let syn::Item::Impl(i) = item else {
+            continue;
+        };
+        let is_default = i
+            .trait_
+            .as_ref()
+            .is_some_and(|(_, path, _)| path.is_ident("Default"));
+        if !is_default {
+            continue;
+        }
+        for item in &i.items {
+            let syn::ImplItem::Fn(f) = item else {
+                continue;
+            };
+            let Some(Stmt::Expr(Expr::Struct(s), None)) = f.block.stmts.last() else {
+                continue;
+            };
+            for field in &s.fields {
+                if let syn::Member::Named(name) = &field.member {
+                    out.insert(name.to_string(), field.expr.clone());
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The canvas size from the "designed at WxH" comment
+fn designed_size(src: &str) -> Option<Vec2> {
+    let rest = &src[src.find("designed at ")? + "designed at ".len()..];
+    let (w, rest) = rest.split_once('x')?;
+    let h: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    Some(vec2(w.parse().ok()?, h.parse().ok()?))
+}
+
+/// The panel and container cell widgets are being read into
+#[derive(Clone, Copy)]
+struct Scope {
+    area: DockArea,
+    /// What absolute rects are relative to, e.g. a tab's content area
+    origin: Vec2,
+    slot: Option<Slot>,
+}
+
+struct Importer {
+    defaults: HashMap<String, Expr>,
+    /// Canvas size anchored rects were generated against
+    design: Vec2,
+    /// Widgets read so far; id 0 means "assign one in `finish`"
+    widgets: Vec<Widget>,
+    warnings: Vec<String>,
+    /// Lines stripped from the top of the source
+    line_offset: usize,
+}
+
+impl Importer {
+    fn warn(&mut self, node: &impl ToTokens, what: &str) {
+        let line = node.span().start().line + self.line_offset;
+        let mut code = node.to_token_stream().to_string();
+        if code.chars().count() > 60 {
+            code = code.chars().take(57).collect::<String>() + "...";
+        }
+        self.warnings.push(format!("line {line}: {what}: {code}"));
+    }
+
+    /// `if state.enable_*` side panels and the central panel
+    fn panels(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            let Some(e) = stmt_expr(stmt) else {
+                self.warn(stmt, "unrecognized statement");
+                continue;
+            };
+            let (area, panel) = match e {
+                Expr::If(i) => {
+                    let area = match state_field(&i.cond).as_deref() {
+                        Some("enable_top") => DockArea::Top,
+                        Some("enable_bottom") => DockArea::Bottom,
+                        Some("enable_left") => DockArea::Left,
+                        Some("enable_right") => DockArea::Right,
+                        _ => {
+                            self.warn(stmt, "unrecognized panel condition");
+                            continue;
+                        }
+                    };
+                    match i.then_branch.stmts.as_slice() {
+                        [stmt] if stmt_expr(stmt).is_some() => (area, stmt_expr(stmt)),
+                        _ => (area, None),
+                    }
+                }
+                e if path_call(chain_root(e))
+                    .is_some_and(|p| p.ends_with("CentralPanel::default")) =>
+                {
+                    (DockArea::Center, Some(e))
+                }
+                _ => (DockArea::Center, None),
+            };
+            let body = panel.and_then(|e| {
+                ["show", "show_inside"]
+                    .iter()
+                    .find_map(|m| method(e, m))
+                    .and_then(|m| m.args.last())
+                    .and_then(closure_body)
+            });
+            let Some(body) = body else {
+                self.warn(stmt, "unrecognized panel");
+                continue;
+            };
+            let scope = Scope {
+                area,
+                origin: Vec2::ZERO,
+                slot: None,
+            };
+            self.flow(body, &scope, Pos2::ZERO, false);
+        }
+    }
+
+    /// Lay out `stmts` from `at` the way egui's flow would, top to bottom or
+    /// left to right, reading absolutely placed widgets among them as they
+    /// are. Returns the area the flow covered.
+    fn flow(&mut self, stmts: &[Stmt], scope: &Scope, at: Pos2, horizontal: bool) -> Rect {
+        let mut cursor = at;
+        let mut covered = Rect::NOTHING;
+        let mut i = 0;
+        while i < stmts.len() {
+            let stmt = &stmts[i];
+            if let Stmt::Local(l) = stmt
+                && matches!(
+                    local_name(l).as_deref(),
+                    Some("canvas" | "tab_origin" | "_")
+                )
+            {
+                i += 1;
+                continue;
+            }
+            let e = stmt_expr(stmt);
+            let call = |name| e.and_then(|e| ui_call(e, name));
+            let placed =
+                if let Some(space) = call("add_space").and_then(|m| m.args.first()).and_then(num) {
+                    if horizontal {
+                        cursor.x += space;
+                    } else {
+                        cursor.y += space;
+                    }
+                    i += 1;
+                    continue;
+                } else if let Some(m) = call("scope_builder") {
+                    self.absolute(m, scope);
+                    i += 1;
+                    continue;
+                } else if let Some(e) = e.filter(|e| is_window(e)) {
+                    self.window(e, scope);
+                    i += 1;
+                    continue;
+                } else if let Some(body) = call("horizontal")
+                    .and_then(|m| m.args.first())
+                    .and_then(closure_body)
+                    .filter(|body| !is_widget_row(body))
+                {
+                    i += 1;
+                    self.flow(body, scope, cursor, true)
+                } else if let Some(body) = call("vertical")
+                    .and_then(|m| m.args.first())
+                    .and_then(closure_body)
+                {
+                    i += 1;
+                    self.flow(body, scope, cursor, false)
+                } else if let Some(e) =
+                    e.filter(|e| grid_id(e).is_some_and(|id| id.starts_with("flow_grid_")))
+                {
+                    i += 1;
+                    self.flow_grid(e, scope, cursor)
+                } else {
+                    let start = self.widgets.len();
+                    let Some(used) = self.widget(&stmts[i..], scope, None) else {
+                        self.warn(stmt, "unrecognized statement");
+                        i += 1;
+                        continue;
+                    };
+                    i += used;
+                    self.translate(start..self.widgets.len(), cursor.to_vec2());
+                    Rect::from_min_size(cursor, self.widgets[start].size)
+                };
+            covered = covered.union(placed);
+            if horizontal {
+                cursor.x = placed.right() + ITEM_SPACING.x;
+            } else {
+                cursor.y = placed.bottom() + ITEM_SPACING.y;
+            }
+        }
+        if covered.is_negative() {
+            Rect::from_min_size(at, Vec2::ZERO)
+        } else {
+            covered
+        }
+    }
+
+    /// `egui::Grid::new("flow_grid_N")`: cells sized to their widest and
+    /// tallest contents, `spacing` apart
+    fn flow_grid(&mut self, e: &Expr, scope: &Scope, at: Pos2) -> Rect {
+        let spacing = method(e, "spacing")
+            .and_then(|m| m.args.first())
+            .and_then(vec2_of)
+            .unwrap_or(ITEM_SPACING);
+        let body = method(e, "show")
+            .and_then(|m| m.args.last())
+            .and_then(closure_body)
+            .unwrap_or_default();
+        let mut cells = Vec::new();
+        for (row, stmts) in grid_rows(body).into_iter().enumerate() {
+            for (col, stmt) in stmts.into_iter().enumerate() {
+                let start = self.widgets.len();
+                let r = self.flow(std::slice::from_ref(stmt), scope, Pos2::ZERO, false);
+                cells.push((row, col, start..self.widgets.len(), r.size()));
+            }
+        }
+        let mut widths: Vec<f32> = Vec::new();
+        let mut heights: Vec<f32> = Vec::new();
+        for (row, col, _, size) in &cells {
+            widths.resize(widths.len().max(col + 1), 0.0);
+            heights.resize(heights.len().max(row + 1), 0.0);
+            widths[*col] = widths[*col].max(size.x);
+            heights[*row] = heights[*row].max(size.y);
+        }
+        let offset =
+            |sizes: &[f32], n: usize, gap: f32| sizes[..n].iter().sum::<f32>() + n as f32 * gap;
+        for (row, col, range, _) in cells {
+            let delta = vec2(
+                at.x + offset(&widths, col, spacing.x),
+                at.y + offset(&heights, row, spacing.y),
+            );
+            self.translate(range, delta);
+        }
+        let size = vec2(
+            offset(&widths, widths.len(), spacing.x) - spacing.x,
+            offset(&heights, heights.len(), spacing.y) - spacing.y,
+        );
+        Rect::from_min_size(at, size.max(Vec2::ZERO))
+    }
+
+    /// `ui.scope_builder(egui::UiBuilder::new().max_rect(rect), |ui| { .. })`
+    fn absolute(&mut self, m: &ExprMethodCall, scope: &Scope) {
+        let rect = m
+            .args
+            .first()
+            .and_then(|b| method(b, "max_rect"))
+            .and_then(|r| r.args.first())
+            .and_then(|r| self.rect(r));
+        let body = m.args.last().and_then(closure_body);
+        let (Some((rect, anchor)), Some(body)) = (rect, body) else {
+            self.warn(m, "unrecognized placement");
+            return;
+        };
+        let start = self.widgets.len();
+        let Some(used) = self.widget(body, scope, Some(rect.size())) else {
+            self.warn(m, "unrecognized widget");
+            return;
+        };
+        for stmt in &body[used..] {
+            self.warn(stmt, "ignored extra statement");
+        }
+        self.widgets[start].anchor = anchor;
+        self.translate(start..self.widgets.len(), scope.origin + rect.min.to_vec2());
+    }
+
+    /// A designed rect and its anchoring, from `rect_expr` output
+    fn rect(&self, e: &Expr) -> Option<(Rect, Anchor)> {
+        let Expr::Call(c) = e else {
+            return None;
+        };
+        let path = path_call(e)?;
+        let args: Vec<&Expr> = c.args.iter().collect();
+        if path.ends_with("Rect::from_min_size") {
+            let min = match args.first()? {
+                Expr::Binary(b) => vec2_of(&b.right)?,
+                _ => return None,
+            };
+            let size = vec2_of(args.get(1)?)?;
+            return Some((Rect::from_min_size(min.to_pos2(), size), Anchor::default()));
+        }
+        if !path.ends_with("Rect::from_min_max") {
+            return None;
+        }
+        // each edge is `canvas.<edge> ± offset`
+        let edges = |e: &Expr| -> Option<[(String, f32); 2]> {
+            let Expr::Call(c) = e else {
+                return None;
+            };
+            let mut out = c.args.iter().map(|a| match a {
+                Expr::Binary(b) => {
+                    let v = num(&b.right)?;
+                    let v = match b.op {
+                        syn::BinOp::Add(_) => v,
+                        syn::BinOp::Sub(_) => -v,
+                        _ => return None,
+                    };
+                    let edge = b.left.to_token_stream().to_string();
+                    let edge = ["min", "max", "center"]
+                        .into_iter()
+                        .find(|k| edge.contains(k))?;
+                    Some((edge.to_owned(), v))
+                }
+                _ => None,
+            });
+            Some([out.next()??, out.next()??])
+        };
+        let [(l, lv), (t, tv)] = edges(args.first()?)?;
+        let [(r, rv), (b, bv)] = edges(args.get(1)?)?;
+        let axis = |from: &str, a: f32, to: &str, b: f32, design: f32| {
+            Some(match (from, to) {
+                ("min", "min") => (0, a, b - a),
+                ("max", "max") => (1, design + a, b - a),
+                ("center", "center") => (2, a + design * 0.5, b - a),
+                ("min", "max") => (3, a, design + b - a),
+                _ => return None,
+            })
+        };
+        let (h, x, w) = axis(&l, lv, &r, rv, self.design.x)?;
+        let (v, y, ht) = axis(&t, tv, &b, bv, self.design.y)?;
+        let anchor = Anchor {
+            h: [
+                HAnchor::Left,
+                HAnchor::Right,
+                HAnchor::Center,
+                HAnchor::Stretch,
+            ][h],
+            v: [
+                VAnchor::Top,
+                VAnchor::Bottom,
+                VAnchor::Center,
+                VAnchor::Stretch,
+            ][v],
+        };
+        Some((Rect::from_min_size(pos2(x, y), vec2(w, ht)), anchor))
+    }
+
+    /// `egui::Window::new(title).default_pos(..).default_size(..)...show(ctx, ..)`
+    fn window(&mut self, e: &Expr, scope: &Scope) {
+        let s = Summary::of(e);
+        let id = s.fields.iter().find_map(|f| {
+            f.strip_prefix("window_")?
+                .strip_suffix("_open")?
+                .parse()
+                .ok()
+        });
+        let mut w = self.new_widget(WidgetKind::Window, id, scope);
+        if let Some(title) = s.strings.first() {
+            w.props.text = title.clone();
+        }
+        let pos = method(e, "default_pos").and_then(|m| match m.args.first()? {
+            Expr::Binary(b) => vec2_of(&b.right),
+            _ => None,
+        });
+        w.pos = (scope.origin + pos.unwrap_or_default()).to_pos2();
+        if let Some(size) = method(e, "default_size")
+            .and_then(|m| m.args.first())
+            .and_then(vec2_of)
+        {
+            w.size = size;
+        }
+        self.widgets.push(w);
+    }
+
+    /// Read the widget drawn by the statements at the start of `stmts`,
+    /// pushing it (and any children) at the local origin. `size` is its
+    /// rect when absolutely placed. Returns how many statements it used.
+    fn widget(&mut self, stmts: &[Stmt], scope: &Scope, size: Option<Vec2>) -> Option<usize> {
+        let first = stmts.first()?;
+        if let Some(used) = self.tab_bar(stmts, scope, size) {
+            return Some(used);
+        }
+        // `let items = ..;` / `let nodes = ..;` belong to the statement after them
+        let (setup, main, used) = match first {
+            Stmt::Local(l) => (Some(l), stmt_expr(stmts.get(1)?)?, 2),
+            stmt => (None, stmt_expr(stmt)?, 1),
+        };
+        let setup_name = setup.and_then(local_name);
+        let setup_init = setup.and_then(|l| l.init.as_ref()).map(|i| &*i.expr);
+        let s = Summary::of(main);
+        // a grid's summary includes its cells, so it's told apart by its id first
+        let grid = grid_id(main).and_then(|id| id.strip_prefix("grid_")?.parse().ok());
+        let (prefix, id) = match grid {
+            Some(id) => ("", Some(id)),
+            None => s
+                .fields
+                .iter()
+                .find_map(|f| split_field(f))
+                .map_or(("", None), |(p, id)| (p, Some(id))),
+        };
+        let id = id.or_else(|| {
+            s.idents
+                .iter()
+                .find_map(|i| i.strip_prefix("clicked_")?.parse().ok())
+        });
+        use WidgetKind::*;
+        let kind = match prefix {
+            "checked" => Checkbox,
+            "text" => TextEdit,
+            "value" => Slider,
+            "progress" => ProgressBar,
+            "sel" if s.called("RadioButton::new") => RadioGroup,
+            "sel" if s.called("ComboBox::from_id_source") || s.called("ComboBox::from_id_salt") => {
+                ComboBox
+            }
+            "sel" if s.called("menu_button") => MenuButton,
+            "sel" => SelectableLabel,
+            "open" => CollapsingHeader,
+            "date" => DatePicker,
+            "pass" => Password,
+            "angle" => AngleSelector,
+            "textarea" => TextArea,
+            "drag" => DragValue,
+            "color" => ColorPicker,
+            "code" => Code,
+            "" if grid.is_some() => Grid,
+            "" if s.called("Button::new") && s.called("format!") => ImageTextButton,
+            "" if s.called("Button::new") => Button,
+            "" if s.called("hyperlink_to") => Hyperlink,
+            "" if s.called("link") => Link,
+            "" if s.called("Spinner::new") => Spinner,
+            "" if s.called("Image::new") => Image,
+            "" if s.called("Frame::group") => Group,
+            "" if s.called("centered_and_justified") => Placeholder,
+            "" if s.called("ScrollArea::both") => ScrollBox,
+            "" if s.called("gen_show_tree") => Tree,
+            "" if s.called("columns") => Columns,
+            "" if s.called("heading") => Heading,
+            "" if s.called("small") => Small,
+            "" if s.called("monospace") => Monospace,
+            "" if s.called("separator") => Separator,
+            "" if s.called("label") => Label,
+            _ => return None,
+        };
+        let expects_setup = matches!(kind, RadioGroup | ComboBox | MenuButton | Tree);
+        if expects_setup != setup.is_some()
+            || (setup.is_some()
+                && setup_name.as_deref() != Some(if kind == Tree { "nodes" } else { "items" }))
+        {
+            return None;
+        }
+
+        let mut w = self.new_widget(kind, id, scope);
+        let field = format!("{prefix}_{}", id.unwrap_or_default());
+        let text = s.strings.first().cloned();
+        let p = &mut w.props;
+        match kind {
+            // the label follows `.suffix("°")`
+            AngleSelector => p.text = s.strings.last().cloned().unwrap_or_default(),
+            Label | Heading | Small | Monospace | Link | Button | Checkbox | Slider | DragValue
+            | SelectableLabel | CollapsingHeader | DatePicker | ColorPicker | MenuButton
+            | Placeholder | ScrollBox | Columns | Group => {
+                p.text = text.unwrap_or_default();
+            }
+            Hyperlink => {
+                p.text = text.unwrap_or_default();
+                p.url = s.strings.get(1).cloned().unwrap_or_default();
+            }
+            ImageTextButton => {
+                p.icon = s.strings.get(1).cloned().unwrap_or_default();
+                p.text = s.strings.get(2).cloned().unwrap_or_default();
+            }
+            TextEdit => p.text = self.default_str(&field).or(text).unwrap_or_default(),
+            Password | TextArea | Code => {
+                if let Some(t) = self.default_str(&field) {
+                    p.text = t;
+                }
+            }
+            Image => {
+                let src = text.unwrap_or_default();
+                if s.called("include_image!") {
+                    self.warnings.push(format!(
+                        "image `{src}` was a project asset; import it again in the Assets tab"
+                    ));
+                }
+                p.url = src;
+            }
+            _ => {}
+        }
+        if let Some(&(min, max)) = s.ranges.first() {
+            (p.min, p.max) = (min, max);
+        }
+        if let Some((label, description)) = s.access.clone() {
+            (p.access_label, p.access_description) = (label, description);
+        }
+        match kind {
+            Checkbox | SelectableLabel | CollapsingHeader => {
+                if let Some(on) = self.default(&field).and_then(lit_bool) {
+                    p.checked = on;
+                }
+            }
+            Slider | DragValue | AngleSelector | ProgressBar => {
+                if let Some(v) = self.default(&field).and_then(num) {
+                    p.value = v;
+                }
+            }
+            RadioGroup | ComboBox | MenuButton => {
+                p.items = setup_init
+                    .map(|e| Summary::of(e).strings)
+                    .unwrap_or_default();
+                if let Some(sel) = self.default(&field).and_then(num) {
+                    p.selected = sel as usize;
+                }
+            }
+            DatePicker => {
+                if let Some([y, m, d]) = self
+                    .default_args(&field, "from_ymd_opt")
+                    .and_then(|a| <[f32; 3]>::try_from(a).ok())
+                {
+                    (p.year, p.month, p.day) = (y as i32, m as u32, d as u32);
+                }
+            }
+            ColorPicker => {
+                if let Some(c) = self.default_args(&field, "from_rgba_unmultiplied") {
+                    p.color = color(&c).unwrap_or(p.color);
+                }
+            }
+            Placeholder => {
+                if let Some(c) = s.args("from_rgba_unmultiplied") {
+                    p.color = color(c).unwrap_or(p.color);
+                }
+            }
+            Columns => {
+                if let Some(&[n]) = s.args("columns") {
+                    p.columns = n as usize;
+                }
+            }
+            Group => p.horizontal = s.called("horizontal"),
+            Tree => {
+                let mut items = Vec::new();
+                if let Some(e) = setup_init {
+                    tree_items(e, 0, &mut items);
+                }
+                p.items = items;
+            }
+            _ => {}
+        }
+
+        // sizes the flow layout can't see come from the widget's own code
+        let sized_by_vec2 = matches!(
+            kind,
+            Button
+                | ImageTextButton
+                | TextEdit
+                | Slider
+                | ProgressBar
+                | Password
+                | AngleSelector
+                | TextArea
+                | Image
+                | Placeholder
+                | Group
+        );
+        let first_vec2 = match s.call("vec2") {
+            Some((_, nums, _)) if sized_by_vec2 => match <[f32; 2]>::try_from(nums.as_slice()) {
+                Ok(size) => Some(Vec2::from(size)),
+                Err(_) => {
+                    self.warn(main, "size isn't two number literals, kept the default");
+                    None
+                }
+            },
+            _ => None,
+        };
+        let own_size = match kind {
+            Button | ImageTextButton | TextEdit | Slider | ProgressBar | Password
+            | AngleSelector | TextArea | Image | Placeholder => first_vec2,
+            Group => first_vec2.map(|v| v + vec2(12.0, 12.0)),
+            ScrollBox => match (s.args("max_width"), s.args("max_height")) {
+                (Some(&[w]), Some(&[h])) => Some(vec2(w + 4.0, h + 4.0)),
+                _ => None,
+            },
+            ComboBox => s.args("width").map(|a| vec2(a[0], w.size.y)),
+            _ => None,
+        };
+        w.size = size.or(own_size).unwrap_or(w.size);
+
+        if kind == Grid {
+            self.grid(w, main, scope);
+        } else {
+            self.widgets.push(w);
+        }
+        Some(used)
+    }
+
+    /// A Grid container and the widgets in its cells
+    fn grid(&mut self, mut grid: Widget, e: &Expr, scope: &Scope) {
+        let s = Summary::of(e);
+        let p = &mut grid.props;
+        if let Some(&[n]) = s.args("num_columns") {
+            p.columns = n as usize;
+        }
+        p.striped = s.flag("striped").unwrap_or(false);
+        if let Some(v) = method(e, "spacing")
+            .and_then(|m| m.args.first())
+            .and_then(vec2_of)
+        {
+            p.spacing = [v.x, v.y];
+        }
+        if let Some(&[w]) = s.args("min_col_width") {
+            p.min_col_width = w;
+        }
+        let body = method(e, "show")
+            .and_then(|m| m.args.last())
+            .and_then(closure_body)
+            .unwrap_or_default();
+        let rows = grid_rows(body);
+        grid.props.rows = rows.len().max(1);
+        let id = grid.id;
+        let cell = |row, col| grid.grid_cell(row, col).min.to_vec2();
+        let cells: Vec<_> = rows
+            .into_iter()
+            .enumerate()
+            .flat_map(|(row, stmts)| {
+                stmts
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(col, stmt)| (row, col, stmt))
+            })
+            .map(|(row, col, stmt)| (row, col, stmt, cell(row, col)))
+            .collect();
+        self.widgets.push(grid);
+        for (row, col, stmt, min) in cells {
+            if is_empty_cell(stmt) {
+                continue;
+            }
+            let scope = Scope {
+                slot: Some(Slot {
+                    parent: id,
+                    row,
+                    col,
+                }),
+                ..*scope
+            };
+            let start = self.widgets.len();
+            self.flow(std::slice::from_ref(stmt), &scope, Pos2::ZERO, false);
+            // children sit at their cell's corner, as the editor snaps them
+            for w in &mut self.widgets[start..] {
+                w.pos = min.to_pos2();
+            }
+        }
+    }
+
+    /// A TabBar container: its header row, separator and `match` on the tab.
+    fn tab_bar(&mut self, stmts: &[Stmt], scope: &Scope, size: Option<Vec2>) -> Option<usize> {
+        let header = stmts.first().and_then(stmt_expr)?;
+        let body = ui_call(header, "horizontal")
+            .and_then(|m| m.args.first())
+            .and_then(closure_body)?;
+        if !is_tab_header(body) {
+            return None;
+        }
+        let s = Summary::of(header);
+        let (_, id) = s.fields.iter().find_map(|f| split_field(f))?;
+        let mut used = 1;
+        if stmts
+            .get(used)
+            .and_then(stmt_expr)
+            .is_some_and(|e| ui_call(e, "separator").is_some())
+        {
+            used += 1;
+        }
+        if matches!(stmts.get(used), Some(Stmt::Local(l)) if local_name(l).as_deref() == Some("tab_origin"))
+        {
+            used += 1;
+        }
+        let arms = match stmts.get(used).and_then(stmt_expr) {
+            Some(Expr::Match(m)) => {
+                used += 1;
+                m.arms.as_slice()
+            }
+            _ => &[],
+        };
+
+        let mut w = self.new_widget(WidgetKind::TabBar, Some(id), scope);
+        w.props.items = s.strings.clone();
+        if let Some(sel) = self.default(&format!("tab_{id}")).and_then(num) {
+            w.props.selected = sel as usize;
+        }
+        w.size = size.unwrap_or(w.size);
+        let content = w.tab_content().min;
+        let tab_id = w.id;
+        self.widgets.push(w);
+        for arm in arms {
+            let syn::Pat::Lit(lit) = &arm.pat else {
+                continue;
+            };
+            let Some(tab) = num(&Expr::Lit(lit.clone())) else {
+                continue;
+            };
+            let Expr::Block(block) = &*arm.body else {
+                continue;
+            };
+            let scope = Scope {
+                origin: content.to_vec2(),
+                slot: Some(Slot {
+                    parent: tab_id,
+                    row: tab as usize,
+                    col: 0,
+                }),
+                ..*scope
+            };
+            self.flow(&block.block.stmts, &scope, content, false);
+        }
+        Some(used)
+    }
+
+    /// A widget of `kind` with its default look, in `scope`
+    fn new_widget(&self, kind: WidgetKind, id: Option<u64>, scope: &Scope) -> Widget {
+        Widget {
+            id: WidgetId::new(id.unwrap_or(0)),
+            kind,
+            pos: Pos2::ZERO,
+            size: kind.default_size(),
+            z: 0,
+            area: scope.area,
+            props: kind.default_props(),
+            anchor: Anchor::default(),
+            slot: scope.slot,
+        }
+    }
+
+    fn translate(&mut self, range: Range<usize>, delta: Vec2) {
+        for w in &mut self.widgets[range] {
+            w.pos += delta;
+        }
+    }
+
+    fn default(&self, field: &str) -> Option<&Expr> {
+        self.defaults.get(field)
+    }
+
+    fn default_str(&self, field: &str) -> Option<String> {
+        Summary::of(self.default(field)?).strings.first().cloned()
+    }
+
+    fn default_args(&self, field: &str, call: &str) -> Option<Vec<f32>> {
+        Summary::of(self.default(field)?)
+            .args(call)
+            .map(<[f32]>::to_vec)
+    }
+
+    /// Give id-less widgets the lowest free ids in code order, so a project
+    /// numbered like the one generated gets its numbering back.
+    fn finish(mut self) -> Imported {
+        let mut seen = HashSet::new();
+        for i in 0..self.widgets.len() {
+            let id = self.widgets[i].id.as_u64();
+            if id != 0 && !seen.insert(id) {
+                self.warnings
+                    .push(format!("widget id {id} is used twice; renumbered"));
+                self.widgets[i].id = WidgetId::new(0);
+            }
+        }
+        let mut next = 1;
+        for w in self.widgets.iter_mut().filter(|w| w.id.as_u64() == 0) {
+            while seen.contains(&next) {
+                next += 1;
+            }
+            w.id = WidgetId::new(next);
+            seen.insert(next);
+        }
+        self.widgets.sort_by_key(|w| w.id);
+        for w in &mut self.widgets {
+            w.z = w.id.as_z();
+        }
+        // children draw above their container
+        let z: HashMap<WidgetId, i32> = self.widgets.iter().map(|w| (w.id, w.z)).collect();
+        for w in &mut self.widgets {
+            if let Some(parent) = w.slot.and_then(|s| z.get(&s.parent)) {
+                w.z = w.z.max(parent + 1);
+            }
+        }
+
+        let mut project = Project {
+            canvas_size: self.design,
+            ..Project::default()
+        };
+        // UI-only output has no `Default`; show the panels that have widgets
+        let enabled = |flag: &str, area: DockArea| {
+            self.defaults
+                .get(flag)
+                .and_then(lit_bool)
+                .unwrap_or_else(|| self.widgets.iter().any(|w| w.area == area))
+        };
+        project.panel_top_enabled = enabled("enable_top", DockArea::Top);
+        project.panel_bottom_enabled = enabled("enable_bottom", DockArea::Bottom);
+        project.panel_left_enabled = enabled("enable_left", DockArea::Left);
+        project.panel_right_enabled = enabled("enable_right", DockArea::Right);
+        project.widgets = self.widgets;
+        Imported {
+            project,
+            warnings: self.warnings,
+        }
+    }
+}
+
+/// What an expression mentions: string literals, `state.` fields, plain
+/// identifiers, calls with their literal arguments, and numeric ranges.
+/// `gen_accessible`'s name and description are kept apart from `strings`.
+#[derive(Default)]
+struct Summary {
+    strings: Vec<String>,
+    access: Option<(String, String)>,
+    fields: Vec<String>,
+    idents: Vec<String>,
+    calls: Vec<(String, Vec<f32>, Vec<bool>)>,
+    ranges: Vec<(f32, f32)>,
+}
+
+impl Summary {
+    fn of(e: &Expr) -> Self {
+        let mut s = Summary::default();
+        s.visit_expr(e);
+        s
+    }
+
+    /// Calls a method, function or macro named `name` (path suffix)
+    fn called(&self, name: &str) -> bool {
+        self.call(name).is_some()
+    }
+
+    fn call(&self, name: &str) -> Option<&(String, Vec<f32>, Vec<bool>)> {
+        self.calls
+            .iter()
+            .find(|(c, ..)| c == name || c.ends_with(&format!("::{name}")))
+    }
+
+    /// Numeric literal arguments of the first call to `name`
+    fn args(&self, name: &str) -> Option<&[f32]> {
+        self.call(name)
+            .map(|(_, nums, _)| nums.as_slice())
+            .filter(|a| !a.is_empty())
+    }
+
+    fn flag(&self, name: &str) -> Option<bool> {
+        self.call(name).and_then(|(_, _, b)| b.first().copied())
+    }
+
+    fn push_call<'a>(&mut self, name: String, args: impl Iterator<Item = &'a Expr>) {
+        let args: Vec<&Expr> = args.collect();
+        let nums = args.iter().filter_map(|a| num(a)).collect();
+        let bools = args.iter().filter_map(|a| lit_bool(a)).collect();
+        self.calls.push((name, nums, bools));
+    }
+}
+
+impl<'ast> Visit<'ast> for Summary {
+    fn visit_lit_str(&mut self, s: &'ast syn::LitStr) {
+        self.strings.push(s.value());
+    }
+
+    fn visit_expr_field(&mut self, e: &'ast syn::ExprField) {
+        if let Some(name) = state_field(&Expr::Field(e.clone())) {
+            self.fields.push(name);
+        }
+        visit::visit_expr_field(self, e);
+    }
+
+    fn visit_expr_path(&mut self, p: &'ast syn::ExprPath) {
+        if let Some(ident) = p.path.get_ident() {
+            self.idents.push(ident.to_string());
+        }
+        visit::visit_expr_path(self, p);
+    }
+
+    fn visit_expr_call(&mut self, c: &'ast syn::ExprCall) {
+        let name = path_call(&Expr::Call(c.clone()));
+        if name.as_deref() == Some("gen_accessible") {
+            let text = |i: usize| Summary::of(c.args.get(i)?).strings.first().cloned();
+            self.access = Some((text(1).unwrap_or_default(), text(2).unwrap_or_default()));
+            if let Some(response) = c.args.first() {
+                self.visit_expr(response);
+            }
+            return;
+        }
+        if let Some(name) = name {
+            self.push_call(name, c.args.iter());
+        }
+        visit::visit_expr_call(self, c);
+    }
+
+    fn visit_expr_method_call(&mut self, m: &'ast syn::ExprMethodCall) {
+        self.push_call(m.method.to_string(), m.args.iter());
+        visit::visit_expr_method_call(self, m);
+    }
+
+    fn visit_expr_range(&mut self, r: &'ast syn::ExprRange) {
+        if let (Some(start), Some(end)) = (
+            r.start.as_deref().and_then(num),
+            r.end.as_deref().and_then(num),
+        ) {
+            self.ranges.push((start, end));
+        }
+        visit::visit_expr_range(self, r);
+    }
+
+    // `vec!`, `format!` and `include_image!` hide their arguments from the syntax tree
+    fn visit_macro(&mut self, m: &'ast syn::Macro) {
+        use syn::parse::Parser;
+        let args = syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated
+            .parse2(m.tokens.clone())
+            .unwrap_or_default();
+        let name = m.path.segments.last().map(|s| s.ident.to_string());
+        self.push_call(format!("{}!", name.unwrap_or_default()), args.iter());
+        for e in &args {
+            self.visit_expr(e);
+        }
+        visit::visit_macro(self, m);
+    }
+}
+
+fn stmt_expr(stmt: &Stmt) -> Option<&Expr> {
+    match stmt {
+        Stmt::Expr(e, _) => Some(e),
+        _ => None,
+    }
+}
+
+fn local_name(l: &syn::Local) -> Option<String> {
+    match &l.pat {
+        syn::Pat::Ident(p) => Some(p.ident.to_string()),
+        syn::Pat::Type(t) => match &*t.pat {
+            syn::Pat::Ident(p) => Some(p.ident.to_string()),
+            _ => None,
+        },
+        syn::Pat::Wild(_) => Some("_".to_owned()),
+        _ => None,
+    }
+}
+
+/// The method call `name` anywhere along `e`'s receiver chain
+fn method<'a>(mut e: &'a Expr, name: &str) -> Option<&'a ExprMethodCall> {
+    while let Expr::MethodCall(m) = e {
+        if m.method == name {
+            return Some(m);
+        }
+        e = &m.receiver;
+    }
+    None
+}
+
+/// `ui.<name>(..)` called directly on `ui`
+fn ui_call<'a>(e: &'a Expr, name: &str) -> Option<&'a ExprMethodCall> {
+    match e {
+        Expr::MethodCall(m)
+            if m.method == name
+                && matches!(&*m.receiver, Expr::Path(p) if p.path.is_ident("ui")) =>
+        {
+            Some(m)
+        }
+        _ => None,
+    }
+}
+
+/// The start of a method chain, e.g. `egui::Grid::new(..)`
+fn chain_root(mut e: &Expr) -> &Expr {
+    while let Expr::MethodCall(m) = e {
+        e = &m.receiver;
+    }
+    e
+}
+
+/// `a::b::c` for a call to a path
+fn path_call(e: &Expr) -> Option<String> {
+    let Expr::Call(c) = e else {
+        return None;
+    };
+    let Expr::Path(p) = &*c.func else {
+        return None;
+    };
+    let segments: Vec<String> = p
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    Some(segments.join("::"))
+}
+
+fn closure_body(e: &Expr) -> Option<&[Stmt]> {
+    match e {
+        Expr::Closure(c) => match &*c.body {
+            Expr::Block(b) => Some(&b.block.stmts),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `x` in `state.x`
+fn state_field(e: &Expr) -> Option<String> {
+    match e {
+        Expr::Field(f) => match (&*f.base, &f.member) {
+            (Expr::Path(base), syn::Member::Named(name)) if base.path.is_ident("state") => {
+                Some(name.to_string())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `text_3` -> ("text", 3)
+fn split_field(field: &str) -> Option<(&str, u64)> {
+    let (prefix, id) = field.rsplit_once('_')?;
+    Some((prefix, id.parse().ok()?))
+}
+
+fn num(e: &Expr) -> Option<f32> {
+    match e {
+        Expr::Lit(l) => match &l.lit {
+            syn::Lit::Float(f) => f.base10_parse().ok(),
+            syn::Lit::Int(i) => i.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Unary(u) if matches!(u.op, syn::UnOp::Neg(_)) => num(&u.expr).map(|v| -v),
+        _ => None,
+    }
+}
+
+fn lit_bool(e: &Expr) -> Option<bool> {
+    match e {
+        Expr::Lit(l) => match &l.lit {
+            syn::Lit::Bool(b) => Some(b.value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `egui::vec2(x, y)`
+fn vec2_of(e: &Expr) -> Option<Vec2> {
+    let Expr::Call(c) = e else {
+        return None;
+    };
+    if !path_call(e)?.ends_with("vec2") {
+        return None;
+    }
+    let mut args = c.args.iter().map(num);
+    Some(vec2(args.next()??, args.next()??))
+}
+
+fn color(rgba: &[f32]) -> Option<[u8; 4]> {
+    let c: [f32; 4] = rgba.try_into().ok()?;
+    Some(c.map(|v| v as u8))
+}
+
+/// The id string of an `egui::Grid::new(..)` chain
+fn grid_id(e: &Expr) -> Option<String> {
+    let root = chain_root(e);
+    if !path_call(root)?.ends_with("Grid::new") {
+        return None;
+    }
+    let Expr::Call(c) = root else {
+        return None;
+    };
+    match c.args.first()? {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(s),
+            ..
+        }) => Some(s.value()),
+        _ => None,
+    }
+}
+
+fn is_window(e: &Expr) -> bool {
+    path_call(chain_root(e)).is_some_and(|p| p.ends_with("Window::new"))
+}
+
+/// Grid body statements split at each `ui.end_row()`
+fn grid_rows(body: &[Stmt]) -> Vec<Vec<&Stmt>> {
+    let mut rows = vec![Vec::new()];
+    for stmt in body {
+        if stmt_expr(stmt).is_some_and(|e| ui_call(e, "end_row").is_some()) {
+            rows.push(Vec::new());
+        } else if let Some(row) = rows.last_mut() {
+            row.push(stmt);
+        }
+    }
+    rows.retain(|r| !r.is_empty());
+    rows
+}
+
+/// `ui.label("")`, which the generator puts in empty grid cells
+fn is_empty_cell(stmt: &Stmt) -> bool {
+    stmt_expr(stmt)
+        .and_then(|e| ui_call(e, "label"))
+        .is_some_and(|m| Summary::of(&m.args[0]).strings == [""])
+}
+
+/// A `ui.horizontal` that draws one widget (a label beside a date picker,
+/// drag value or color button, or a tab header) rather than a flow row
+fn is_widget_row(body: &[Stmt]) -> bool {
+    if is_tab_header(body) {
+        return true;
+    }
+    match body {
+        [label, control] => {
+            let labelled = stmt_expr(label).is_some_and(|e| ui_call(e, "label").is_some());
+            let control = stmt_expr(control).map(Summary::of);
+            let field = control
+                .iter()
+                .flat_map(|s| &s.fields)
+                .find_map(|f| split_field(f));
+            labelled && matches!(field, Some(("date" | "drag" | "color", _)))
+        }
+        _ => false,
+    }
+}
+
+fn is_tab_header(body: &[Stmt]) -> bool {
+    !body.is_empty()
+        && body.iter().all(|s| {
+            stmt_expr(s)
+                .and_then(|e| ui_call(e, "selectable_value"))
+                .is_some_and(|m| {
+                    matches!(
+                        m.args.first(),
+                        Some(Expr::Reference(r)) if state_field(&r.expr).is_some_and(|f| f.starts_with("tab_"))
+                    )
+                })
+        })
+}
+
+/// Tree items (two spaces of indent per level) from nested `GenTreeNode`s
+fn tree_items(e: &Expr, depth: usize, out: &mut Vec<String>) {
+    let Expr::Macro(m) = e else {
+        return;
+    };
+    use syn::parse::Parser;
+    let nodes = syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated
+        .parse2(m.mac.tokens.clone())
+        .unwrap_or_default();
+    for node in &nodes {
+        let Expr::Struct(s) = node else {
+            continue;
+        };
+        for field in &s.fields {
+            match &field.member {
+                syn::Member::Named(n) if n == "label" => {
+                    if let Some(label) = Summary::of(&field.expr).strings.first() {
+                        out.push(format!("{}{label}", "  ".repeat(depth)));
+                    }
+                }
+                syn::Member::Named(n) if n == "children" => tree_items(&field.expr, depth + 1, out),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_warns_about_unknown_code() {
+        let src = r#"
+fn generated_ui(ctx: &egui::Context, state: &mut GeneratedState) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let canvas = ui.max_rect();
+        ui.scope_builder(
+            egui::UiBuilder::new().max_rect(egui::Rect::from_min_size(canvas.min + egui::vec2(10.0, 20.0), egui::vec2(160.0, 32.0))),
+            |ui| {
+                ui.add_sized(egui::vec2(160.0, 32.0), egui::Button::new("Go"));
+            },
+        );
+        ui.add(MyWidget::new());
+    });
+}
+"#;
+        let imported = import_code(src).unwrap();
+        let [w] = imported.project.widgets.as_slice() else {
+            panic!("expected one widget");
+        };
+        assert_eq!(w.kind, WidgetKind::Button);
+        assert_eq!(w.props.text, "Go");
+        assert_eq!((w.pos, w.size), (pos2(10.0, 20.0), vec2(160.0, 32.0)));
+        assert_eq!(imported.warnings.len(), 1);
+        assert!(
+            imported.warnings[0].starts_with("line 11: unrecognized statement"),
+            "{:?}",
+            imported.warnings
+        );
+
+        assert!(import_code("fn main() {}").is_err());
+        assert!(import_code("fn (").is_err_and(|e| e.starts_with("line 1:")));
+    }
+
+    #[test]
+    fn test_import_hand_edited_size() {
+        let src = r#"
+fn generated_ui(ctx: &egui::Context, state: &mut GeneratedState) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let width = ui.available_width();
+        ui.add_sized(egui::vec2(width, 32.0), egui::Button::new("Go"));
+    });
+}
+"#;
+        let imported = import_code(src).unwrap();
+        let [w] = imported.project.widgets.as_slice() else {
+            panic!("expected one widget: {:?}", imported.warnings);
+        };
+        assert_eq!(w.kind, WidgetKind::Button);
+        assert_eq!(w.size, WidgetKind::Button.default_size());
+        assert!(
+            imported
+                .warnings
+                .iter()
+                .any(|w| w.starts_with("line 5: size isn't two number literals")),
+            "{:?}",
+            imported.warnings
+        );
+    }
+
+    #[test]
+    fn test_import_anchored_rect() {
+        let importer = Importer {
+            defaults: HashMap::new(),
+            design: vec2(400.0, 300.0),
+            widgets: Vec::new(),
+            warnings: Vec::new(),
+            line_offset: 0,
+        };
+        let rect: Expr = syn::parse_quote!(egui::Rect::from_min_max(
+            egui::pos2(canvas.min.x + 10.0, canvas.max.y - 280.0),
+            egui::pos2(canvas.max.x - 290.0, canvas.max.y - 250.0)
+        ));
+        let (rect, anchor) = importer.rect(&rect).unwrap();
+        assert_eq!(
+            rect,
+            Rect::from_min_size(pos2(10.0, 20.0), vec2(100.0, 30.0))
+        );
+        assert_eq!(
+            anchor,
+            Anchor {
+                h: HAnchor::Stretch,
+                v: VAnchor::Bottom
+            }
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Flow-layout inference: turns absolutely placed widgets into rows and grids
+//! so generated code can use `ui.horizontal` / `egui::Grid` instead of rects.
+
+use crate::widget::{Widget, WidgetId, WidgetKind};
+use egui::{Rect, Vec2, vec2};
+
+/// egui's default `item_spacing`; gaps beyond it become `ui.add_space`.
+pub(crate) const ITEM_SPACING: Vec2 = vec2(8.0, 3.0);
+/// How far apart column edges may be and still count as aligned.
+const ALIGN_TOLERANCE: f32 = 6.0;
+/// Left offsets up to this are treated as the panel's own margin.
+const EDGE_MARGIN: f32 = 12.0;
+/// Rects may touch or overlap by this much without counting as overlapping.
+const OVERLAP_TOLERANCE: f32 = 2.0;
+
+/// Why a widget kept its absolute placement in flow mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Fallback {
+    /// Anchored widgets follow the panel edges, which a flow can't express
+    Anchored,
+    /// Overlaps a widget that was already placed in the flow
+    Overlapping,
+}
+
+impl Fallback {
+    pub const fn reason(&self) -> &'static str {
+        match self {
+            Fallback::Anchored => "anchored to the panel edges",
+            Fallback::Overlapping => "overlaps another widget",
+        }
+    }
+}
+
+/// Widgets laid out left to right. Each cell carries the extra horizontal
+/// space to add before it (beyond `ITEM_SPACING.x`).
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct FlowRow {
+    pub(crate) cells: Vec<(f32, WidgetId)>,
+}
+
+impl FlowRow {
+    /// A single widget with nothing to indent it: no `ui.horizontal` needed.
+    pub(crate) fn is_plain(&self) -> bool {
+        matches!(self.cells.as_slice(), [(lead, _)] if *lead < 1.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum FlowItem {
+    Row(FlowRow),
+    /// Consecutive rows whose columns line up
+    Grid {
+        rows: Vec<Vec<WidgetId>>,
+        spacing: Vec2,
+        /// Horizontal space before the grid
+        indent: f32,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct FlowLayout {
+    /// Items top to bottom, each with the extra vertical space before it
+    pub(crate) items: Vec<(f32, FlowItem)>,
+    /// Widgets that still need absolute placement
+    pub(crate) fallback: Vec<(WidgetId, Fallback)>,
+}
+
+struct Row<'a> {
+    top: f32,
+    bottom: f32,
+    widgets: Vec<&'a Widget>,
+}
+
+fn rect(w: &Widget) -> Rect {
+    Rect::from_min_size(w.pos, w.size)
+}
+
+/// Infer rows, grids and spacing from the designed positions of `widgets`
+/// (all from the same panel). Windows float on their own and are ignored.
+pub(crate) fn infer(widgets: &[&Widget]) -> FlowLayout {
+    let mut layout = FlowLayout::default();
+    let mut sorted: Vec<&Widget> = widgets
+        .iter()
+        .copied()
+        .filter(|w| w.kind != WidgetKind::Window)
+        .collect();
+    sorted.sort_by(|a, b| {
+        (a.pos.y, a.pos.x)
+            .partial_cmp(&(b.pos.y, b.pos.x))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut placed: Vec<&Widget> = Vec::new();
+    for w in sorted {
+        // anchors outside the central panel are ignored, so those flow
+        if !w.anchor.is_fixed() && w.is_anchorable() {
+            layout.fallback.push((w.id, Fallback::Anchored));
+        } else if placed.iter().any(|p| {
+            let overlap = rect(p).intersect(rect(w));
+            overlap.width() > OVERLAP_TOLERANCE && overlap.height() > OVERLAP_TOLERANCE
+        }) {
+            layout.fallback.push((w.id, Fallback::Overlapping));
+        } else {
+            placed.push(w);
+        }
+    }
+
+    // Group into rows: a widget joins the current row when most of its
+    // height sits inside the row's vertical span.
+    let mut rows: Vec<Row> = Vec::new();
+    for w in placed {
+        let r = rect(w);
+        match rows.last_mut() {
+            Some(row)
+                if row.bottom.min(r.bottom()) - row.top.max(r.top())
+                    > 0.5 * r.height().min(row.bottom - row.top) =>
+            {
+                row.top = row.top.min(r.top());
+                row.bottom = row.bottom.max(r.bottom());
+                row.widgets.push(w);
+            }
+            _ => rows.push(Row {
+                top: r.top(),
+                bottom: r.bottom(),
+                widgets: vec![w],
+            }),
+        }
+    }
+    for row in &mut rows {
+        row.widgets.sort_by(|a, b| a.pos.x.total_cmp(&b.pos.x));
+    }
+
+    let mut prev_bottom = 0.0;
+    let mut i = 0;
+    while i < rows.len() {
+        // Extend a grid run while rows have the same aligned columns
+        let mut j = i + 1;
+        while j < rows.len() && columns_align(&rows[i], &rows[j]) {
+            j += 1;
+        }
+        let gap = if i == 0 {
+            rows[i].top - prev_bottom
+        } else {
+            rows[i].top - prev_bottom - ITEM_SPACING.y
+        };
+        let space = gap.max(0.0).round();
+        if j - i >= 2 {
+            let run = &rows[i..j];
+            layout.items.push((space, grid(run)));
+            prev_bottom = run.last().map_or(prev_bottom, |r| r.bottom);
+            i = j;
+        } else {
+            let row = &rows[i];
+            let mut x = 0.0;
+            let cells = row
+                .widgets
+                .iter()
+                .enumerate()
+                .map(|(k, w)| {
+                    let gap = if k == 0 {
+                        indent(w.pos.x)
+                    } else {
+                        w.pos.x - x - ITEM_SPACING.x
+                    };
+                    x = w.pos.x + w.size.x;
+                    (gap.max(0.0).round(), w.id)
+                })
+                .collect();
+            layout.items.push((space, FlowItem::Row(FlowRow { cells })));
+            prev_bottom = row.bottom;
+            i += 1;
+        }
+    }
+    layout
+}
+
+/// Two rows of 2+ widgets whose left edges line up column by column.
+fn columns_align(a: &Row, b: &Row) -> bool {
+    a.widgets.len() >= 2
+        && a.widgets.len() == b.widgets.len()
+        && a.widgets
+            .iter()
+            .zip(&b.widgets)
+            .all(|(x, y)| (x.pos.x - y.pos.x).abs() <= ALIGN_TOLERANCE)
+}
+
+fn grid(run: &[Row]) -> FlowItem {
+    let cols = run[0].widgets.len();
+    // Column gap: the tightest space between a cell and the next column's start
+    let mut sx = f32::INFINITY;
+    for c in 1..cols {
+        let start = run
+            .iter()
+            .map(|r| r.widgets[c].pos.x)
+            .fold(f32::INFINITY, f32::min);
+        let end = run
+            .iter()
+            .map(|r| r.widgets[c - 1].pos.x + r.widgets[c - 1].size.x)
+            .fold(f32::NEG_INFINITY, f32::max);
+        sx = sx.min(start - end);
+    }
+    let sy = run
+        .windows(2)
+        .map(|p| p[1].top - p[0].bottom)
+        .fold(f32::INFINITY, f32::min);
+    FlowItem::Grid {
+        rows: run
+            .iter()
+            .map(|r| r.widgets.iter().map(|w| w.id).collect())
+            .collect(),
+        spacing: vec2(sx.max(0.0).round(), sy.max(0.0).round()),
+        indent: indent(
+            run.iter()
+                .map(|r| r.widgets[0].pos.x)
+                .fold(f32::INFINITY, f32::min),
+        ),
+    }
+}
+
+fn indent(x: f32) -> f32 {
+    if x <= EDGE_MARGIN { 0.0 } else { x.round() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::{HAnchor, WidgetKind};
+    use egui::pos2;
+
+    fn widget(id: u64, x: f32, y: f32, w: f32, h: f32) -> Widget {
+        let kind = WidgetKind::Button;
+        Widget {
+            id: WidgetId::new(id),
+            kind,
+            pos: pos2(x, y),
+            size: vec2(w, h),
+            z: 0,
+            area: crate::widget::DockArea::Center,
+            props: kind.default_props(),
+            anchor: Default::default(),
+            slot: None,
+        }
+    }
+
+    fn ids(ids: &[u64]) -> Vec<WidgetId> {
+        ids.iter().map(|&i| WidgetId::new(i)).collect()
+    }
+
+    #[test]
+    fn test_infer_rows() {
+        let ws = [
+            widget(1, 10.0, 0.0, 100.0, 20.0),
+            widget(2, 0.0, 40.0, 60.0, 20.0),
+            widget(3, 80.0, 42.0, 60.0, 20.0),
+        ];
+        let layout = infer(&ws.iter().collect::<Vec<_>>());
+        assert!(layout.fallback.is_empty());
+        assert_eq!(layout.items.len(), 2);
+        let FlowItem::Row(first) = &layout.items[0].1 else {
+            panic!("expected a row");
+        };
+        assert!(first.is_plain());
+        // 40 - 20 - item spacing
+        assert_eq!(layout.items[1].0, 17.0);
+        assert_eq!(
+            layout.items[1].1,
+            FlowItem::Row(FlowRow {
+                cells: vec![(0.0, WidgetId::new(2)), (12.0, WidgetId::new(3))]
+            })
+        );
+    }
+
+    #[test]
+    fn test_infer_grid() {
+        let ws = [
+            widget(1, 40.0, 0.0, 60.0, 20.0),
+            widget(2, 130.0, 0.0, 60.0, 20.0),
+            widget(3, 42.0, 30.0, 60.0, 20.0),
+            widget(4, 130.0, 30.0, 60.0, 20.0),
+        ];
+        let layout = infer(&ws.iter().collect::<Vec<_>>());
+        assert_eq!(
+            layout.items,
+            vec![(
+                0.0,
+                FlowItem::Grid {
+                    rows: vec![ids(&[1, 2]), ids(&[3, 4])],
+                    spacing: vec2(28.0, 10.0),
+                    indent: 40.0,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_infer_fallback() {
+        let mut anchored = widget(3, 0.0, 200.0, 50.0, 20.0);
+        anchored.anchor.h = HAnchor::Right;
+        let ws = [
+            widget(1, 0.0, 0.0, 100.0, 40.0),
+            widget(2, 50.0, 10.0, 100.0, 40.0),
+            anchored,
+        ];
+        let layout = infer(&ws.iter().collect::<Vec<_>>());
+        assert_eq!(
+            layout.fallback,
+            vec![
+                (WidgetId::new(2), Fallback::Overlapping),
+                (WidgetId::new(3), Fallback::Anchored),
+            ]
+        );
+        assert_eq!(layout.items.len(), 1);
+    }
+}
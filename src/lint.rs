@@ -0,0 +1,417 @@
+//! Design checks: problems in a project that show up as overlapping or
+//! hidden widgets, odd generated code, or a UI that's hard to use with a
+//! screen reader or poor eyesight, listed in the Problems tab.
+
+use crate::{
+    project::Project,
+    widget::{DockArea, Slot, Widget, WidgetId, WidgetKind},
+};
+use egui::{Color32, Rect, Vec2};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    /// Probably not what was meant
+    Warning,
+    /// Broken, in the builder or the generated code
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Issue {
+    pub(crate) severity: Severity,
+    /// The widget to select to fix it
+    pub(crate) widget: WidgetId,
+    pub(crate) message: String,
+}
+
+/// Widgets with visible text that say nothing when it's empty
+const TEXT_KINDS: [WidgetKind; 10] = [
+    WidgetKind::Label,
+    WidgetKind::Heading,
+    WidgetKind::Small,
+    WidgetKind::Monospace,
+    WidgetKind::Button,
+    WidgetKind::ImageTextButton,
+    WidgetKind::Link,
+    WidgetKind::Hyperlink,
+    WidgetKind::SelectableLabel,
+    WidgetKind::Checkbox,
+];
+
+/// Widgets the user clicks or drags, which need room to hit
+const CLICK_KINDS: [WidgetKind; 15] = [
+    WidgetKind::Button,
+    WidgetKind::ImageTextButton,
+    WidgetKind::Checkbox,
+    WidgetKind::Link,
+    WidgetKind::Hyperlink,
+    WidgetKind::SelectableLabel,
+    WidgetKind::TextEdit,
+    WidgetKind::Password,
+    WidgetKind::Slider,
+    WidgetKind::AngleSelector,
+    WidgetKind::ComboBox,
+    WidgetKind::MenuButton,
+    WidgetKind::DragValue,
+    WidgetKind::DatePicker,
+    WidgetKind::ColorPicker,
+];
+
+/// WCAG's minimum contrast ratio for body text
+const MIN_CONTRAST: f32 = 4.5;
+
+/// Checks every widget, errors first. Widgets in the Center and Free areas
+/// must fit the canvas; those in side panels must fit `panel_size` of their
+/// panel, when it's known. Relative asset paths resolve against `base`.
+/// Text contrast is judged in `visuals`, the theme the design is previewed in.
+pub(crate) fn lint(
+    project: &Project,
+    base: Option<&Path>,
+    visuals: &egui::Visuals,
+    panel_size: &dyn Fn(DockArea) -> Option<Vec2>,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for w in &project.widgets {
+        check_widget(project, base, visuals, panel_size, w, &mut issues);
+    }
+    check_overlaps(project, &mut issues);
+    // stable, so each severity keeps widget order
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    issues
+}
+
+fn check_widget(
+    project: &Project,
+    base: Option<&Path>,
+    visuals: &egui::Visuals,
+    panel_size: &dyn Fn(DockArea) -> Option<Vec2>,
+    w: &Widget,
+    issues: &mut Vec<Issue>,
+) {
+    let mut report = |severity, message: String| {
+        issues.push(Issue {
+            severity,
+            widget: w.id,
+            message,
+        })
+    };
+
+    let (panel, enabled) = match w.area {
+        DockArea::Top => ("top", project.panel_top_enabled),
+        DockArea::Bottom => ("bottom", project.panel_bottom_enabled),
+        DockArea::Left => ("left", project.panel_left_enabled),
+        DockArea::Right => ("right", project.panel_right_enabled),
+        DockArea::Center | DockArea::Free => ("central", true),
+    };
+    if !enabled {
+        report(
+            Severity::Warning,
+            format!("is in the {panel} panel, which is disabled, so it isn't shown"),
+        );
+    }
+    if !w.anchor.is_fixed() && !w.is_anchorable() {
+        report(
+            Severity::Warning,
+            format!(
+                "is anchored in the {panel} panel, where only the central panel's anchors apply"
+            ),
+        );
+    }
+    // children are placed by their container
+    if w.slot.is_none() {
+        let bounds = match w.area {
+            DockArea::Center | DockArea::Free => Some(project.canvas_size),
+            area => panel_size(area).filter(|_| enabled),
+        };
+        let rect = Rect::from_min_size(w.pos, w.size);
+        if let Some(bounds) = bounds
+            && !Rect::from_min_size(egui::Pos2::ZERO, bounds).contains_rect(rect)
+        {
+            let within = if panel == "central" {
+                "the canvas".to_owned()
+            } else {
+                format!("the {panel} panel")
+            };
+            report(
+                Severity::Warning,
+                format!("extends outside {within} ({:.0}×{:.0})", bounds.x, bounds.y),
+            );
+        }
+    }
+
+    let text = w.props.text.trim();
+    // a symbol such as "✖" or an image button's icon, with no words
+    let icon_only = match w.kind {
+        WidgetKind::ImageTextButton => text.is_empty() && !w.props.icon.trim().is_empty(),
+        WidgetKind::Button => !text.is_empty() && !text.chars().any(char::is_alphanumeric),
+        _ => false,
+    };
+    if TEXT_KINDS.contains(&w.kind) && text.is_empty() && !icon_only {
+        report(Severity::Warning, "has no text".into());
+    }
+    if icon_only && w.props.access_label.trim().is_empty() {
+        report(
+            Severity::Warning,
+            "shows only an icon, with no accessible name for screen readers".into(),
+        );
+    }
+    let target = egui::Style::default().spacing.interact_size;
+    if CLICK_KINDS.contains(&w.kind) && (w.size.x < target.x || w.size.y < target.y) {
+        report(
+            Severity::Warning,
+            format!(
+                "is {:.0}×{:.0}, smaller than egui's {:.0}×{:.0} click target",
+                w.size.x, w.size.y, target.x, target.y
+            ),
+        );
+    }
+    match w.kind {
+        WidgetKind::ComboBox | WidgetKind::RadioGroup => {
+            let items = w.props.items.len();
+            if items == 0 {
+                report(Severity::Warning, "has no items to choose from".into());
+            } else if w.props.selected >= items {
+                report(
+                    Severity::Error,
+                    format!("selects item {} but has only {items}", w.props.selected + 1),
+                );
+            }
+        }
+        // a label drawn over the fill in the theme's text color
+        WidgetKind::Placeholder if !text.is_empty() => {
+            let [r, g, b, a] = w.props.color;
+            let fill = Color32::from_rgba_unmultiplied(r, g, b, a);
+            let background = visuals.panel_fill.blend(fill);
+            let ratio = contrast_ratio(visuals.text_color(), background);
+            if ratio < MIN_CONTRAST {
+                report(
+                    Severity::Warning,
+                    format!("has text contrast {ratio:.1}:1 on its fill, below {MIN_CONTRAST}:1"),
+                );
+            }
+        }
+        WidgetKind::Slider if w.props.min >= w.props.max => {
+            report(
+                Severity::Error,
+                format!("has min {} not below max {}", w.props.min, w.props.max),
+            );
+        }
+        WidgetKind::Image => match w.props.asset {
+            Some(id) => match project.asset(id) {
+                None => report(Severity::Error, "uses an asset that was removed".into()),
+                Some(asset) if !asset.exists(base) => report(
+                    Severity::Error,
+                    format!("uses asset {}, whose file is missing", asset.name),
+                ),
+                Some(_) => {}
+            },
+            None => {
+                if let Some(path) = w.props.url.strip_prefix("file://")
+                    && !Path::new(path).is_file()
+                {
+                    report(Severity::Error, format!("points to missing file {path}"));
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+/// WCAG contrast ratio between two opaque colors, from 1 (none) to 21
+fn contrast_ratio(a: Color32, b: Color32) -> f32 {
+    // relative luminance, from linear RGB
+    let luminance = |c: Color32| {
+        let linear = |v: u8| {
+            let v = v as f32 / 255.0;
+            if v <= 0.04045 {
+                v / 12.92
+            } else {
+                ((v + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(c.r()) + 0.7152 * linear(c.g()) + 0.0722 * linear(c.b())
+    };
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// Widgets drawn over each other: in the same panel, or the same container
+/// cell or tab
+fn check_overlaps(project: &Project, issues: &mut Vec<Issue>) {
+    // Center and Free share the central panel
+    let place = |w: &Widget| -> (DockArea, Option<Slot>) {
+        let area = match w.area {
+            DockArea::Free => DockArea::Center,
+            area => area,
+        };
+        (area, w.slot)
+    };
+    let widgets = &project.widgets;
+    for (i, a) in widgets.iter().enumerate() {
+        let rect_a = Rect::from_min_size(a.pos, a.size);
+        for b in &widgets[i + 1..] {
+            let rect_b = Rect::from_min_size(b.pos, b.size);
+            let overlap = rect_a.intersect(rect_b);
+            if place(a) == place(b) && overlap.width() > 0.0 && overlap.height() > 0.0 {
+                issues.push(Issue {
+                    severity: Severity::Warning,
+                    widget: b.id,
+                    message: format!("overlaps #{} {}", a.id, a.kind.display_name()),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Anchor;
+    use egui::{pos2, vec2};
+
+    fn widget(id: u64, kind: WidgetKind, pos: egui::Pos2) -> Widget {
+        Widget {
+            id: WidgetId::new(id),
+            kind,
+            pos,
+            size: vec2(100.0, 30.0),
+            z: id as i32,
+            area: DockArea::Center,
+            props: kind.default_props(),
+            anchor: Anchor::default(),
+            slot: None,
+        }
+    }
+
+    fn messages(project: &Project) -> Vec<(u64, Severity, String)> {
+        let panel = |_| Some(vec2(700.0, 40.0));
+        lint(project, None, &egui::Visuals::dark(), &panel)
+            .into_iter()
+            .map(|i| (i.widget.as_u64(), i.severity, i.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_project_has_no_issues() {
+        let mut project = Project::default();
+        project
+            .widgets
+            .push(widget(1, WidgetKind::Button, pos2(10.0, 10.0)));
+        project
+            .widgets
+            .push(widget(2, WidgetKind::Label, pos2(10.0, 50.0)));
+        assert!(messages(&project).is_empty());
+    }
+
+    #[test]
+    fn test_lint_checks() {
+        let mut project = Project::default();
+        let mut button = widget(1, WidgetKind::Button, pos2(10.0, 10.0));
+        button.props.text = "  ".into();
+        let overlapping = widget(2, WidgetKind::Label, pos2(60.0, 20.0));
+        let mut combo = widget(3, WidgetKind::ComboBox, pos2(10.0, 100.0));
+        combo.props.items = vec!["A".into()];
+        combo.props.selected = 3;
+        let mut slider = widget(4, WidgetKind::Slider, pos2(10.0, 150.0));
+        slider.props.min = 5.0;
+        slider.props.max = 5.0;
+        let outside = widget(5, WidgetKind::Label, pos2(650.0, 200.0));
+        let mut docked = widget(6, WidgetKind::Label, pos2(0.0, 0.0));
+        docked.area = DockArea::Top;
+        let mut image = widget(7, WidgetKind::Image, pos2(10.0, 300.0));
+        image.props.url = "file:///no/such/image.png".into();
+        let mut radio = widget(8, WidgetKind::RadioGroup, pos2(10.0, 400.0));
+        radio.props.items.clear();
+        let mut anchored = widget(9, WidgetKind::Label, pos2(10.0, 0.0));
+        anchored.area = DockArea::Bottom;
+        anchored.anchor.h = crate::widget::HAnchor::Right;
+        project.widgets = vec![
+            button,
+            overlapping,
+            combo,
+            slider,
+            outside,
+            docked,
+            image,
+            radio,
+            anchored,
+        ];
+
+        use Severity::*;
+        assert_eq!(
+            messages(&project),
+            vec![
+                (3, Error, "selects item 4 but has only 1".into()),
+                (4, Error, "has min 5 not below max 5".into()),
+                (7, Error, "points to missing file /no/such/image.png".into()),
+                (1, Warning, "has no text".into()),
+                (5, Warning, "extends outside the canvas (700×600)".into()),
+                (
+                    6,
+                    Warning,
+                    "is in the top panel, which is disabled, so it isn't shown".into()
+                ),
+                (8, Warning, "has no items to choose from".into()),
+                (
+                    9,
+                    Warning,
+                    "is in the bottom panel, which is disabled, so it isn't shown".into()
+                ),
+                (
+                    9,
+                    Warning,
+                    "is anchored in the bottom panel, where only the central panel's anchors apply"
+                        .into()
+                ),
+                (2, Warning, "overlaps #1 Button".into()),
+            ]
+        );
+
+        // an enabled panel bounds its widgets instead
+        project.panel_top_enabled = true;
+        project.widgets[5].pos = pos2(0.0, 20.0);
+        let issues = messages(&project);
+        assert!(issues.contains(&(6, Warning, "extends outside the top panel (700×40)".into())));
+    }
+
+    #[test]
+    fn test_accessibility_checks() {
+        let mut project = Project::default();
+        let mut icon = widget(1, WidgetKind::ImageTextButton, pos2(10.0, 10.0));
+        icon.props.text.clear();
+        let mut symbol = widget(2, WidgetKind::Button, pos2(10.0, 50.0));
+        symbol.props.text = "✖".into();
+        let mut named = widget(3, WidgetKind::Button, pos2(10.0, 90.0));
+        named.props.text = "🗑".into();
+        named.props.access_label = "Delete".into();
+        let mut small = widget(4, WidgetKind::Checkbox, pos2(10.0, 130.0));
+        small.size = vec2(30.0, 12.0);
+        let mut faint = widget(5, WidgetKind::Placeholder, pos2(10.0, 170.0));
+        faint.props.color = [120, 120, 120, 255];
+        let mut dark = widget(6, WidgetKind::Placeholder, pos2(10.0, 210.0));
+        dark.props.color = [0, 0, 0, 255];
+        project.widgets = vec![icon, symbol, named, small, faint, dark];
+
+        use Severity::*;
+        let unnamed = "shows only an icon, with no accessible name for screen readers";
+        assert_eq!(
+            messages(&project),
+            vec![
+                (1, Warning, unnamed.into()),
+                (2, Warning, unnamed.into()),
+                (
+                    4,
+                    Warning,
+                    "is 30×12, smaller than egui's 40×18 click target".into()
+                ),
+                (
+                    5,
+                    Warning,
+                    "has text contrast 1.3:1 on its fill, below 4.5:1".into()
+                ),
+            ]
+        );
+        assert!((contrast_ratio(Color32::BLACK, Color32::WHITE) - 21.0).abs() < 0.01);
+    }
+}
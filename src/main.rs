@@ -1,8 +1,17 @@
 //! A lightweight RAD GUI builder for `egui` written in Rust.
 
 mod app;
+mod assets;
+mod codegen;
+mod diff;
 mod highlight;
+mod import;
+mod layout;
+mod lint;
+mod prefs;
 mod project;
+mod recovery;
+mod search;
 mod widget;
 
 use crate::app::RadBuilderApp;
@@ -43,6 +52,10 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "egui RAD GUI Builder",
         native_options,
-        Box::new(|_cc| Ok(Box::<RadBuilderApp>::default())),
+        Box::new(|cc| {
+            // Image widgets and the asset panel preview files through these loaders
+            egui_extras::install_image_loaders(&cc.egui_ctx);
+            Ok(Box::new(RadBuilderApp::new(cc)))
+        }),
     )
 }
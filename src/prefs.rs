@@ -0,0 +1,142 @@
+//! Preferences kept between sessions in eframe's storage. They belong to
+//! the builder, not to any project.
+
+use crate::codegen::CodeGenFormat;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Most recently used projects kept in File > Recent Projects
+const MAX_RECENT: usize = 10;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Prefs {
+    /// Code panel syntax theme while the builder is in dark mode
+    pub(crate) dark_theme: Option<String>,
+    /// Code panel syntax theme while the builder is in light mode
+    pub(crate) light_theme: Option<String>,
+    pub(crate) grid_size: f32,
+    pub(crate) show_grid: bool,
+    pub(crate) palette_open: bool,
+    pub(crate) syntax_highlighting: bool,
+    pub(crate) auto_generate: bool,
+    pub(crate) codegen_format: CodeGenFormat,
+    pub(crate) codegen_comments: bool,
+    /// Project files, most recently opened or saved first
+    pub(crate) recent_files: Vec<PathBuf>,
+    /// Open the most recent project on startup
+    pub(crate) reopen_last: bool,
+}
+
+impl Default for Prefs {
+    fn default() -> Self {
+        Self {
+            dark_theme: None,
+            light_theme: None,
+            grid_size: 1.0,
+            show_grid: false,
+            palette_open: true,
+            syntax_highlighting: true,
+            auto_generate: false,
+            codegen_format: CodeGenFormat::default(),
+            codegen_comments: true,
+            recent_files: Vec::new(),
+            reopen_last: false,
+        }
+    }
+}
+
+impl Prefs {
+    /// The preferences eframe kept from the last session, or defaults
+    pub(crate) fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, self);
+    }
+
+    /// The syntax theme chosen for dark or light mode
+    pub(crate) fn theme(&self, dark: bool) -> Option<&str> {
+        if dark {
+            self.dark_theme.as_deref()
+        } else {
+            self.light_theme.as_deref()
+        }
+    }
+
+    pub(crate) fn set_theme(&mut self, dark: bool, name: String) {
+        if dark {
+            self.dark_theme = Some(name);
+        } else {
+            self.light_theme = Some(name);
+        }
+    }
+
+    /// Move `path` to the top of the recent projects
+    pub(crate) fn add_recent(&mut self, path: &Path) {
+        self.recent_files.retain(|p| p != path);
+        self.recent_files.insert(0, path.to_path_buf());
+        self.recent_files.truncate(MAX_RECENT);
+    }
+}
+
+/// The builder's directory for files it keeps for itself, like autosaves,
+/// in the platform's data directory
+pub(crate) fn data_dir() -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        env_dir("LOCALAPPDATA").or_else(|| env_dir("APPDATA"))?
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME")?.join("Library/Application Support")
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| Some(env_dir("HOME")?.join(".local/share")))?
+    };
+    Some(dir.join("egui-rad-builder"))
+}
+
+/// A directory named by an environment variable, if it's set
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_files() {
+        let mut prefs = Prefs::default();
+        for i in 0..12 {
+            prefs.add_recent(Path::new(&format!("p{i}.json")));
+        }
+        prefs.add_recent(Path::new("p5.json"));
+        assert_eq!(prefs.recent_files.len(), MAX_RECENT);
+        assert_eq!(prefs.recent_files[0], Path::new("p5.json"));
+        assert_eq!(prefs.recent_files[1], Path::new("p11.json"));
+        assert_eq!(
+            prefs
+                .recent_files
+                .iter()
+                .filter(|p| *p == Path::new("p5.json"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_missing_prefs_use_defaults() {
+        let prefs: Prefs = serde_json::from_str(r#"{"dark_theme": "Solarized (dark)"}"#).unwrap();
+        assert_eq!(prefs.theme(true), Some("Solarized (dark)"));
+        assert_eq!(
+            prefs,
+            Prefs {
+                dark_theme: prefs.dark_theme.clone(),
+                ..Prefs::default()
+            }
+        );
+    }
+}
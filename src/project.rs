@@ -1,26 +1,436 @@
-use crate::widget::Widget;
+use crate::assets::{Asset, AssetId, AssetKind};
+use crate::widget::{Widget, WidgetId, WidgetKind};
 use egui::{Vec2, vec2};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt;
 
+/// Version of the saved project format. Bump it, and add a step to
+/// `MIGRATIONS`, whenever a change needs more than serde defaults to
+/// read older files.
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades a saved project from one schema version to the next
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[v]` upgrades version `v` to `v + 1`
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [unversioned];
+
+/// 0 → 1: files from before `schema_version` existed. Everything added
+/// since the first release has a serde default; only tab bars need
+/// rewriting, as they were a 32 px high strip before they held content,
+/// so they're grown to the current default height.
+fn unversioned(project: &mut Map<String, Value>) -> Result<(), String> {
+    let Some(Value::Array(widgets)) = project.get_mut("widgets") else {
+        return Ok(());
+    };
+    let height = f64::from(WidgetKind::TabBar.default_size().y);
+    for widget in widgets {
+        if widget.pointer("/kind/t").and_then(Value::as_str) != Some("TabBar") {
+            continue;
+        }
+        if let Some(y) = widget.pointer_mut("/size/y")
+            && y.as_f64().is_some_and(|y| y < height)
+        {
+            *y = height.into();
+        }
+    }
+    Ok(())
+}
+
+/// The JSON of a saved project, upgraded to `SCHEMA_VERSION`
+fn upgraded(json: &str) -> Result<Value, LoadError> {
+    let mut value: Value = serde_json::from_str(json).map_err(|e| LoadError::serde(json, &e))?;
+    let Value::Object(fields) = &mut value else {
+        return Err(LoadError::new("not a project: expected a JSON object"));
+    };
+    let version = match fields.get("schema_version") {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| LoadError::new(format!("invalid schema_version {v}")))?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(LoadError::new(format!(
+            "this project was saved by a newer egui-rad-builder (schema version \
+             {version}; this version reads up to {SCHEMA_VERSION}). Please update \
+             egui-rad-builder to open it."
+        )));
+    }
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migrate(fields)
+            .map_err(|e| LoadError::new(format!("upgrading from schema version {from}: {e}")))?;
+    }
+    fields.insert("schema_version".into(), SCHEMA_VERSION.into());
+    Ok(value)
+}
+
+/// `widget 3 (id 7, Button)`, from whatever of it is readable
+fn describe_widget(index: usize, value: &Value) -> String {
+    let id = value.get("id").map(|id| format!("id {id}"));
+    let kind = value
+        .pointer("/kind/t")
+        .and_then(Value::as_str)
+        .map(str::to_owned);
+    let details: Vec<String> = id.into_iter().chain(kind).collect();
+    if details.is_empty() {
+        format!("widget {}", index + 1)
+    } else {
+        format!("widget {} ({})", index + 1, details.join(", "))
+    }
+}
+
+/// Why a project couldn't be read
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct LoadError {
+    pub(crate) message: String,
+    /// 1-based line and column in the JSON, when the problem has one
+    pub(crate) location: Option<(usize, usize)>,
+    /// The lines up to `location`, numbered, with a caret under the column
+    pub(crate) snippet: String,
+}
+
+impl LoadError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            location: None,
+            snippet: String::new(),
+        }
+    }
+
+    /// `err` from parsing `json`
+    fn serde(json: &str, err: &serde_json::Error) -> Self {
+        let (line, column) = (err.line(), err.column());
+        if line == 0 {
+            return Self::new(err.to_string());
+        }
+        let message = err.to_string();
+        let position = format!(" at line {line} column {column}");
+        let message = message.strip_suffix(&position).unwrap_or(&message);
+
+        let first = line.saturating_sub(2).max(1);
+        let width = line.to_string().len();
+        let mut snippet = String::new();
+        let mut caret = 0;
+        for (n, text) in json
+            .lines()
+            .enumerate()
+            .skip(first - 1)
+            .take(line + 1 - first)
+        {
+            snippet += &format!("{:>width$} | {text}\n", n + 1);
+            // serde_json counts bytes
+            let before = column.saturating_sub(1);
+            caret = text.get(..before).map_or(before, |s| s.chars().count());
+        }
+        snippet += &format!("{:width$} | {:caret$}^", "", "");
+        Self {
+            message: message.to_owned(),
+            location: Some((line, column)),
+            snippet,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(f, "{} (line {line}, column {column})", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// A project read leniently, and what had to be left out to read it
+pub(crate) struct Salvaged {
+    pub(crate) project: Project,
+    pub(crate) problems: Vec<String>,
+}
+
+/// Missing fields take their value from `Project::default()`, so files
+/// written before a field existed still load.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct Project {
+    /// Format the project was saved in; always `SCHEMA_VERSION` once loaded
+    pub(crate) schema_version: u32,
     pub(crate) widgets: Vec<Widget>,
     pub(crate) canvas_size: Vec2,
     pub(crate) panel_top_enabled: bool,
     pub(crate) panel_bottom_enabled: bool,
     pub(crate) panel_left_enabled: bool,
     pub(crate) panel_right_enabled: bool,
+    /// Images and fonts referenced by the design
+    pub(crate) assets: Vec<Asset>,
+    /// Bumped by `touch` on every edit, so views of the design know when
+    /// to refresh. Not saved.
+    #[serde(skip)]
+    pub(crate) revision: u64,
 }
 
 impl Default for Project {
     fn default() -> Self {
         Self {
+            schema_version: SCHEMA_VERSION,
             widgets: Vec::new(),
             canvas_size: vec2(700.0, 600.0),
             panel_top_enabled: false,
             panel_bottom_enabled: false,
             panel_left_enabled: false,
             panel_right_enabled: false,
+            assets: Vec::new(),
+            revision: 0,
+        }
+    }
+}
+
+impl Project {
+    /// Reads a saved project, upgrading it from older schema versions
+    pub(crate) fn from_json(json: &str) -> Result<Self, LoadError> {
+        let value = upgraded(json)?;
+        serde_json::from_value(value).map_err(|e| {
+            // errors from a `Value` have no position; reading the text again
+            // finds the same problem with one
+            match serde_json::from_str::<Project>(json) {
+                Err(located) if located.line() > 0 => LoadError::serde(json, &located),
+                _ => LoadError::new(e.to_string()),
+            }
+        })
+    }
+
+    /// Reads what it can of a saved project, skipping widgets and fields
+    /// that don't read (an unknown widget kind, say) instead of failing.
+    /// Only unreadable JSON or a newer schema still fail.
+    pub(crate) fn from_json_lenient(json: &str) -> Result<Salvaged, LoadError> {
+        let Value::Object(mut fields) = upgraded(json)? else {
+            unreachable!("`upgraded` checks for an object");
+        };
+        let mut problems = Vec::new();
+        let widgets = match fields.remove("widgets") {
+            Some(Value::Array(widgets)) => widgets,
+            None => Vec::new(),
+            Some(_) => {
+                problems.push("`widgets` isn't a list; no widgets were read".to_owned());
+                Vec::new()
+            }
+        };
+        // every field has a default, so each one can be tried on its own
+        fields.retain(|key, value| {
+            let alone = Map::from_iter([(key.clone(), value.clone())]);
+            match serde_json::from_value::<Project>(Value::Object(alone)) {
+                Ok(_) => true,
+                Err(e) => {
+                    problems.push(format!("field `{key}` ignored: {e}"));
+                    false
+                }
+            }
+        });
+        let mut project: Project = serde_json::from_value(Value::Object(fields))
+            .map_err(|e| LoadError::new(e.to_string()))?;
+
+        for (i, value) in widgets.into_iter().enumerate() {
+            let describe = describe_widget(i, &value);
+            match serde_json::from_value::<Widget>(value) {
+                Ok(widget) => project.widgets.push(widget),
+                Err(e) => {
+                    // unknown kinds list every known one; the name is enough
+                    let e = e.to_string();
+                    let reason = e.split(", expected").next().unwrap_or(&e);
+                    problems.push(format!("{describe} skipped: {reason}"));
+                }
+            }
+        }
+        let ids: HashSet<WidgetId> = project.widgets.iter().map(|w| w.id).collect();
+        for w in &mut project.widgets {
+            if let Some(slot) = w.slot.filter(|s| !ids.contains(&s.parent)) {
+                w.slot = None;
+                problems.push(format!(
+                    "widget id {} taken out of missing container {}",
+                    w.id, slot.parent
+                ));
+            }
+        }
+        Ok(Salvaged { project, problems })
+    }
+
+    /// Record an edit
+    pub(crate) fn touch(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    pub(crate) fn asset(&self, id: AssetId) -> Option<&Asset> {
+        self.assets.iter().find(|a| a.id == id)
+    }
+
+    pub(crate) fn next_asset_id(&self) -> AssetId {
+        let max = self.assets.iter().map(|a| a.id).max();
+        AssetId::new(max.map_or(1, |id| id.get() + 1))
+    }
+
+    /// Why asset `id` can't be called `name`, if it can't. Font names are
+    /// family names, so a blank or repeated one would be unusable.
+    pub(crate) fn asset_name_problem(&self, id: AssetId, name: &str) -> Option<&'static str> {
+        if name.trim().is_empty() {
+            Some("Name can't be empty")
+        } else if self.assets.iter().any(|a| a.id != id && a.name == name) {
+            Some("Another asset already has this name")
+        } else {
+            None
+        }
+    }
+
+    /// `stem`, or `stem_2`, `stem_3`, … when another asset has that name
+    pub(crate) fn unique_asset_name(&self, stem: &str) -> String {
+        let taken = |name: &str| self.assets.iter().any(|a| a.name == name);
+        if !taken(stem) {
+            return stem.to_owned();
+        }
+        (2..)
+            .map(|n| format!("{stem}_{n}"))
+            .find(|name| !taken(name))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn fonts(&self) -> impl Iterator<Item = &Asset> {
+        self.assets.iter().filter(|a| a.kind == AssetKind::Font)
+    }
+
+    /// Image asset used by a widget, if it references a valid one.
+    pub(crate) fn widget_image(&self, w: &Widget) -> Option<&Asset> {
+        w.props
+            .asset
+            .and_then(|id| self.asset(id))
+            .filter(|a| a.kind == AssetKind::Image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BROKEN: &str = r#"{
+  "schema_version": 1,
+  "canvas_size": "wide",
+  "widgets": [
+    {"id": 1, "kind": {"t": "Group"}, "pos": {"x": 0.0, "y": 0.0},
+     "size": {"x": 200.0, "y": 100.0}, "props": {}},
+    {"id": 2, "kind": {"t": "Knob"}, "pos": {"x": 0.0, "y": 0.0},
+     "size": {"x": 40.0, "y": 40.0}, "props": {}},
+    {"id": 3, "kind": {"t": "Label"}, "pos": {"x": 10.0, "y": 10.0},
+     "size": {"x": 60.0, "y": 20.0}, "props": {"text": "Inside"},
+     "slot": {"parent": 2, "row": 0, "col": 0}}
+  ]
+}"#;
+
+    #[test]
+    fn test_load_errors_point_into_the_file() {
+        let err = Project::from_json("{\n  \"widgets\": [\n    1,,\n  ]\n}").unwrap_err();
+        assert_eq!(err.location, Some((3, 7)));
+        assert_eq!(err.message, "expected value");
+        assert_eq!(
+            err.snippet,
+            "1 | {\n2 |   \"widgets\": [\n3 |     1,,\n  |       ^"
+        );
+
+        let err = Project::from_json(BROKEN).unwrap_err();
+        assert_eq!(err.location.map(|(line, _)| line), Some(3));
+        assert!(err.to_string().contains("line 3"), "{err}");
+    }
+
+    #[test]
+    fn test_lenient_load_skips_what_it_cannot_read() {
+        let salvaged = Project::from_json_lenient(BROKEN).unwrap();
+        let ids: Vec<_> = salvaged.project.widgets.iter().map(|w| w.id).collect();
+        assert_eq!(ids, [WidgetId::new(1), WidgetId::new(3)]);
+        assert_eq!(salvaged.project.widgets[1].slot, None);
+        assert_eq!(salvaged.project.canvas_size, Project::default().canvas_size);
+        assert_eq!(salvaged.problems.len(), 3, "{:?}", salvaged.problems);
+        assert!(salvaged.problems[0].starts_with("field `canvas_size` ignored"));
+        assert_eq!(
+            salvaged.problems[1],
+            "widget 2 (id 2, Knob) skipped: unknown variant `Knob`"
+        );
+        assert!(salvaged.problems[2].contains("missing container 2"));
+
+        // syntax errors can't be skipped
+        assert!(Project::from_json_lenient("{").is_err());
+    }
+
+    #[test]
+    fn test_load_older_and_newer_schemas() {
+        // as saved before schema_version, assets, anchors and slots existed
+        let unversioned = r#"{
+            "widgets": [{
+                "id": 1,
+                "kind": {"t": "Button"},
+                "pos": {"x": 10.0, "y": 20.0},
+                "size": {"x": 80.0, "y": 24.0},
+                "z": 1,
+                "area": "Center",
+                "props": {"text": "Go", "checked": false}
+            }, {
+                "id": 2,
+                "kind": {"t": "TabBar"},
+                "pos": {"x": 10.0, "y": 60.0},
+                "size": {"x": 300.0, "y": 32.0},
+                "z": 2,
+                "area": "Center",
+                "props": {"items": ["One", "Two"]}
+            }],
+            "canvas_size": {"x": 640.0, "y": 480.0},
+            "panel_top_enabled": true,
+            "panel_bottom_enabled": false,
+            "panel_left_enabled": false,
+            "panel_right_enabled": false
+        }"#;
+        let project = Project::from_json(unversioned).unwrap();
+        assert_eq!(project.schema_version, SCHEMA_VERSION);
+        assert_eq!(project.widgets[0].props.text, "Go");
+        assert_eq!(project.widgets[0].size, vec2(80.0, 24.0));
+        // tab bars from before they held content get room for it
+        let tabs = &project.widgets[1];
+        assert_eq!(tabs.size, vec2(300.0, WidgetKind::TabBar.default_size().y));
+        assert!(tabs.tab_content().height() > 100.0);
+        assert!(project.assets.is_empty());
+        assert!(project.panel_top_enabled);
+
+        // a minimal file falls back to defaults
+        let project = Project::from_json(r#"{"schema_version": 1}"#).unwrap();
+        assert_eq!(project.canvas_size, Project::default().canvas_size);
+
+        let saved = serde_json::to_string(&Project::default()).unwrap();
+        assert!(saved.contains(&format!("\"schema_version\":{SCHEMA_VERSION}")));
+        let newer = saved.replace(
+            &format!("\"schema_version\":{SCHEMA_VERSION}"),
+            &format!("\"schema_version\":{}", SCHEMA_VERSION + 1),
+        );
+        let err = Project::from_json(&newer).unwrap_err();
+        assert!(err.message.contains("newer egui-rad-builder"), "{err}");
+        assert!(Project::from_json("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_asset_names() {
+        let mut project = Project::default();
+        for (id, name) in [(1, "Regular"), (2, "Regular_2")] {
+            project.assets.push(Asset {
+                id: AssetId::new(id),
+                kind: AssetKind::Font,
+                name: name.into(),
+                path: format!("{name}.ttf"),
+                data: None,
+            });
         }
+        assert_eq!(project.unique_asset_name("Bold"), "Bold");
+        assert_eq!(project.unique_asset_name("Regular"), "Regular_3");
+
+        let id = AssetId::new(1);
+        assert_eq!(project.asset_name_problem(id, "Regular"), None);
+        assert_eq!(project.asset_name_problem(id, "Body"), None);
+        assert!(project.asset_name_problem(id, " ").is_some());
+        assert!(project.asset_name_problem(id, "Regular_2").is_some());
     }
 }
@@ -0,0 +1,153 @@
+//! Autosaved copies of unsaved work, so a crash doesn't lose the edits
+//! made since the last Save.
+
+use crate::{prefs, project::Project};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Serialize, Deserialize)]
+struct Autosave {
+    /// File the project was last opened from or saved to
+    file: Option<PathBuf>,
+    /// Kept as JSON so it's read through `Project::from_json`'s migrations
+    project: serde_json::Value,
+}
+
+/// Unsaved work found on startup
+pub(crate) struct Recovery {
+    pub(crate) project: Project,
+    pub(crate) file: Option<PathBuf>,
+    /// When it was autosaved
+    pub(crate) saved_at: SystemTime,
+    /// Autosave it was read from
+    path: PathBuf,
+}
+
+impl Recovery {
+    /// Keep the work to offer again on the next start, out of the way of
+    /// this session's autosaves (replacing any older work set aside)
+    pub(crate) fn set_aside(&self) {
+        if let Some(aside) = aside_path()
+            && self.path != aside
+        {
+            let _ = std::fs::rename(&self.path, aside);
+        }
+    }
+
+    /// The work was restored: this session's autosaves take over from it
+    pub(crate) fn restored(&self) {
+        if let Some(path) = path()
+            && self.path != path
+        {
+            let _ = std::fs::rename(&self.path, path);
+        }
+    }
+
+    /// The work was discarded
+    pub(crate) fn discard(&self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// `recovery.json` in the builder's data directory
+fn path() -> Option<PathBuf> {
+    Some(prefs::data_dir()?.join("recovery.json"))
+}
+
+/// Where work waits that the user put off deciding about
+fn aside_path() -> Option<PathBuf> {
+    Some(prefs::data_dir()?.join("recovery.previous.json"))
+}
+
+/// Autosave `project`, last saved to `file`
+pub(crate) fn save(project: &Project, file: Option<&Path>) -> Result<(), String> {
+    save_to(&path().ok_or("no data directory")?, project, file)
+}
+
+/// Autosaved work that's newer than the file it was last saved to, from
+/// the last session or else from one set aside before it
+pub(crate) fn load() -> Option<Recovery> {
+    load_from(&path()?).or_else(|| load_from(&aside_path()?))
+}
+
+/// Forget this session's autosave, once its work is saved
+pub(crate) fn clear() {
+    if let Some(path) = path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn save_to(path: &Path, project: &Project, file: Option<&Path>) -> Result<(), String> {
+    let autosave = Autosave {
+        file: file.map(Path::to_path_buf),
+        project: serde_json::to_value(project).map_err(|e| e.to_string())?,
+    };
+    let json = serde_json::to_string(&autosave).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    // write then rename, so a crash mid-write keeps the previous autosave
+    let partial = path.with_extension("json.partial");
+    std::fs::write(&partial, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, path).map_err(|e| e.to_string())
+}
+
+fn load_from(path: &Path) -> Option<Recovery> {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let saved_at = modified(path)?;
+    let autosave: Autosave = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    // a file saved after the autosave already has the work, or replaced it
+    if let Some(file_time) = autosave.file.as_deref().and_then(modified)
+        && file_time >= saved_at
+    {
+        return None;
+    }
+    let project = Project::from_json(&autosave.project.to_string()).ok()?;
+    Some(Recovery {
+        project,
+        file: autosave.file,
+        saved_at,
+        path: path.to_path_buf(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_only_when_newer_than_the_save() {
+        let dir = std::env::temp_dir().join(format!("rad_recovery_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let autosave = dir.join("recovery.json");
+        let file = dir.join("project.json");
+        let project = Project {
+            panel_left_enabled: true,
+            ..Project::default()
+        };
+
+        let set_modified = |path: &Path, time: SystemTime| {
+            let f = std::fs::File::options().write(true).open(path).unwrap();
+            f.set_modified(time).unwrap();
+        };
+        let second = std::time::Duration::from_secs(1);
+
+        std::fs::write(&file, "{}").unwrap();
+        save_to(&autosave, &project, Some(&file)).unwrap();
+        let saved_at = std::fs::metadata(&autosave).unwrap().modified().unwrap();
+        set_modified(&file, saved_at - second);
+        let recovery = load_from(&autosave).expect("autosave is newer than the file");
+        assert!(recovery.project.panel_left_enabled);
+        assert_eq!(recovery.file.as_deref(), Some(file.as_path()));
+
+        set_modified(&file, saved_at + second);
+        assert!(load_from(&autosave).is_none(), "file was saved since");
+
+        // never saved to a file
+        save_to(&autosave, &project, None).unwrap();
+        assert!(load_from(&autosave).is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
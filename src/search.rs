@@ -0,0 +1,246 @@
+//! Finding text in the generated code and in the widgets' own strings:
+//! their text, list items, URLs and tooltips.
+
+use crate::{
+    project::Project,
+    widget::{Widget, WidgetId, WidgetKind},
+};
+use std::ops::Range;
+
+/// Byte ranges of the non-overlapping occurrences of `needle` in `haystack`
+pub(crate) fn find_all(haystack: &str, needle: &str, match_case: bool) -> Vec<Range<usize>> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    if match_case {
+        return haystack
+            .match_indices(needle)
+            .map(|(at, found)| at..at + found.len())
+            .collect();
+    }
+    let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+    let mut found = Vec::new();
+    let mut from = 0;
+    for (at, _) in haystack.char_indices() {
+        if at < from {
+            continue;
+        }
+        let mut rest = haystack[at..].char_indices();
+        let mut end = at;
+        let matched = needle.chars().all(|n| match rest.next() {
+            Some((i, h)) if same(h, n) => {
+                end = at + i + h.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if matched {
+            found.push(at..end);
+            from = end;
+        }
+    }
+    found
+}
+
+/// `haystack` with every occurrence of `needle` replaced, and how many
+/// there were
+pub(crate) fn replace_all(
+    haystack: &str,
+    needle: &str,
+    replacement: &str,
+    match_case: bool,
+) -> (String, usize) {
+    let found = find_all(haystack, needle, match_case);
+    let mut out = String::with_capacity(haystack.len());
+    let mut last = 0;
+    for range in &found {
+        out.push_str(&haystack[last..range.start]);
+        out.push_str(replacement);
+        last = range.end;
+    }
+    out.push_str(&haystack[last..]);
+    (out, found.len())
+}
+
+/// A widget string searched by `search_project`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Field {
+    Text,
+    /// A list item, tab or tree node, by index
+    Item(usize),
+    Url,
+    Tooltip,
+}
+
+impl Field {
+    pub(crate) fn describe(self) -> String {
+        match self {
+            Field::Text => "text".into(),
+            Field::Item(i) => format!("item {}", i + 1),
+            Field::Url => "URL".into(),
+            Field::Tooltip => "tooltip".into(),
+        }
+    }
+}
+
+/// A widget string containing the search text
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Hit {
+    pub(crate) widget: WidgetId,
+    pub(crate) kind: WidgetKind,
+    pub(crate) field: Field,
+    pub(crate) value: String,
+}
+
+/// The strings a widget shows or uses, leaving out props its kind ignores
+/// (every widget has a default URL, say, but only links use it)
+fn fields(w: &Widget) -> Vec<(Field, &String)> {
+    let mut fields = Vec::new();
+    if !matches!(
+        w.kind,
+        WidgetKind::Separator | WidgetKind::Spinner | WidgetKind::ProgressBar
+    ) {
+        fields.push((Field::Text, &w.props.text));
+    }
+    if matches!(
+        w.kind,
+        WidgetKind::RadioGroup
+            | WidgetKind::ComboBox
+            | WidgetKind::Tree
+            | WidgetKind::MenuButton
+            | WidgetKind::TabBar
+    ) {
+        fields.extend(
+            w.props
+                .items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (Field::Item(i), item)),
+        );
+    }
+    if w.kind == WidgetKind::Hyperlink || (w.kind == WidgetKind::Image && w.props.asset.is_none()) {
+        fields.push((Field::Url, &w.props.url));
+    }
+    fields.push((Field::Tooltip, &w.props.tooltip));
+    fields
+}
+
+/// Every widget string containing `needle`, in widget order
+pub(crate) fn search_project(project: &Project, needle: &str, match_case: bool) -> Vec<Hit> {
+    project
+        .widgets
+        .iter()
+        .flat_map(|w| {
+            fields(w)
+                .into_iter()
+                .filter(|(_, value)| !find_all(value, needle, match_case).is_empty())
+                .map(|(field, value)| Hit {
+                    widget: w.id,
+                    kind: w.kind,
+                    field,
+                    value: value.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Replaces `needle` in every string `search_project` would find it in,
+/// returning the number of replacements
+pub(crate) fn replace_in_project(
+    project: &mut Project,
+    needle: &str,
+    replacement: &str,
+    match_case: bool,
+) -> usize {
+    let hits = search_project(project, needle, match_case);
+    let mut count = 0;
+    for hit in hits {
+        let Some(w) = project.widgets.iter_mut().find(|w| w.id == hit.widget) else {
+            continue;
+        };
+        let value = match hit.field {
+            Field::Text => &mut w.props.text,
+            Field::Item(i) => &mut w.props.items[i],
+            Field::Url => &mut w.props.url,
+            Field::Tooltip => &mut w.props.tooltip,
+        };
+        let (replaced, n) = replace_all(value, needle, replacement, match_case);
+        *value = replaced;
+        count += n;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::{Anchor, DockArea};
+
+    fn widget(id: u64, kind: WidgetKind) -> Widget {
+        Widget {
+            id: WidgetId::new(id),
+            kind,
+            pos: egui::pos2(0.0, id as f32 * 40.0),
+            size: kind.default_size(),
+            z: id as i32,
+            area: DockArea::Center,
+            props: kind.default_props(),
+            anchor: Anchor::default(),
+            slot: None,
+        }
+    }
+
+    #[test]
+    fn test_find_all_ignoring_case() {
+        assert_eq!(find_all("Save, SAVE, save", "save", true), vec![12..16]);
+        assert_eq!(
+            find_all("Save, SAVE, save", "save", false),
+            vec![0..4, 6..10, 12..16]
+        );
+        assert_eq!(find_all("aaaa", "aa", false), vec![0..2, 2..4]);
+        assert_eq!(find_all("Größe GRÖSSE", "größe", false), vec![0..7]);
+        assert!(find_all("anything", "", false).is_empty());
+        assert_eq!(
+            replace_all("Ok ok OK", "ok", "Done", false),
+            ("Done Done Done".into(), 3)
+        );
+    }
+
+    #[test]
+    fn test_search_and_replace_project() {
+        let mut project = Project::default();
+        let mut button = widget(1, WidgetKind::Button);
+        button.props.text = "Save file".into();
+        button.props.tooltip = "Save the file to disk".into();
+        let mut combo = widget(2, WidgetKind::ComboBox);
+        combo.props.items = vec!["Open file".into(), "Close".into()];
+        // not a link, so its default URL isn't searched
+        let mut label = widget(3, WidgetKind::Label);
+        label.props.url = "file:///tmp".into();
+        project.widgets = vec![button, combo, label];
+
+        let hits = search_project(&project, "FILE", false);
+        let found: Vec<_> = hits.iter().map(|h| (h.widget, h.field)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (WidgetId::new(1), Field::Text),
+                (WidgetId::new(1), Field::Tooltip),
+                (WidgetId::new(2), Field::Item(0))
+            ]
+        );
+        assert!(search_project(&project, "FILE", true).is_empty());
+
+        assert_eq!(
+            replace_in_project(&mut project, "file", "document", true),
+            3
+        );
+        assert_eq!(project.widgets[0].props.text, "Save document");
+        assert_eq!(
+            project.widgets[0].props.tooltip,
+            "Save the document to disk"
+        );
+        assert_eq!(project.widgets[1].props.items[0], "Open document");
+        assert_eq!(project.widgets[2].props.url, "file:///tmp");
+    }
+}
@@ -1,4 +1,5 @@
-use egui::{Pos2, Vec2, pos2};
+use crate::assets::AssetId;
+use egui::{Pos2, Rect, Vec2, pos2, vec2};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -10,6 +11,10 @@ impl WidgetId {
         Self(id)
     }
 
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+
     pub const fn as_z(&self) -> i32 {
         self.0 as i32
     }
@@ -32,6 +37,96 @@ pub(crate) enum DockArea {
     Center,
 }
 
+/// Where a child widget sits inside a container widget
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Slot {
+    pub(crate) parent: WidgetId,
+    /// Grid row, or tab index for tab bars
+    pub(crate) row: usize,
+    /// Grid column (always 0 for tab bars)
+    pub(crate) col: usize,
+}
+
+/// Height of a tab bar's header strip; tab content starts below it
+const TAB_HEADER_HEIGHT: f32 = 28.0;
+
+/// How a widget's horizontal edges follow its panel when the panel is resized
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub(crate) enum HAnchor {
+    #[default]
+    Left,
+    Right,
+    Center,
+    Stretch,
+}
+
+/// How a widget's vertical edges follow its panel when the panel is resized
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub(crate) enum VAnchor {
+    #[default]
+    Top,
+    Bottom,
+    Center,
+    Stretch,
+}
+
+/// Smallest size a stretched widget shrinks to
+const MIN_ANCHORED_SIZE: Vec2 = vec2(16.0, 12.0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub(crate) struct Anchor {
+    pub(crate) h: HAnchor,
+    pub(crate) v: VAnchor,
+}
+
+impl Anchor {
+    /// Plain top-left placement (what every widget had before anchors existed)
+    pub(crate) fn is_fixed(&self) -> bool {
+        *self == Anchor::default()
+    }
+
+    pub(crate) fn stretches(&self) -> bool {
+        self.h == HAnchor::Stretch || self.v == VAnchor::Stretch
+    }
+
+    /// Move/resize a widget laid out in a container of size `old` so it keeps
+    /// its anchoring once the container becomes `new`.
+    pub(crate) fn apply(&self, pos: Pos2, size: Vec2, old: Vec2, new: Vec2) -> (Pos2, Vec2) {
+        let d = new - old;
+        let (x, w) = match self.h {
+            HAnchor::Left => (pos.x, size.x),
+            HAnchor::Right => (pos.x + d.x, size.x),
+            HAnchor::Center => (pos.x + d.x * 0.5, size.x),
+            HAnchor::Stretch => (pos.x, (size.x + d.x).max(MIN_ANCHORED_SIZE.x)),
+        };
+        let (y, h) = match self.v {
+            VAnchor::Top => (pos.y, size.y),
+            VAnchor::Bottom => (pos.y + d.y, size.y),
+            VAnchor::Center => (pos.y + d.y * 0.5, size.y),
+            VAnchor::Stretch => (pos.y, (size.y + d.y).max(MIN_ANCHORED_SIZE.y)),
+        };
+        (pos2(x, y), vec2(w, h))
+    }
+}
+
+impl HAnchor {
+    pub(crate) const ALL: [HAnchor; 4] = [
+        HAnchor::Left,
+        HAnchor::Right,
+        HAnchor::Center,
+        HAnchor::Stretch,
+    ];
+}
+
+impl VAnchor {
+    pub(crate) const ALL: [VAnchor; 4] = [
+        VAnchor::Top,
+        VAnchor::Bottom,
+        VAnchor::Center,
+        VAnchor::Stretch,
+    ];
+}
+
 /// Widget categories for palette organization (Mobius-ECS inspired)
 #[allow(dead_code)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -73,15 +168,97 @@ impl WidgetCategory {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct Widget {
     pub(crate) id: WidgetId,
     pub(crate) kind: WidgetKind,
     pub(crate) pos: Pos2,  // Top-left relative to canvas
     pub(crate) size: Vec2, // Desired size on canvas
-    pub(crate) z: i32,     // draw order
+    #[serde(default)]
+    pub(crate) z: i32, // draw order
+    #[serde(default)]
     pub(crate) area: DockArea,
     pub(crate) props: WidgetProps,
+    #[serde(default)]
+    pub(crate) anchor: Anchor,
+    /// Container cell this widget lives in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) slot: Option<Slot>,
+}
+
+impl Widget {
+    /// A central-panel widget with its kind's default size and props
+    #[cfg(test)]
+    pub(crate) fn for_test(id: u64, kind: WidgetKind, pos: Pos2) -> Self {
+        Self {
+            id: WidgetId::new(id),
+            kind,
+            pos,
+            size: kind.default_size(),
+            z: id as i32,
+            area: DockArea::Center,
+            props: kind.default_props(),
+            anchor: Anchor::default(),
+            slot: None,
+        }
+    }
+
+    /// Anchors follow the canvas rect, which only the central panel has
+    pub(crate) fn is_anchorable(&self) -> bool {
+        matches!(self.area, DockArea::Center | DockArea::Free)
+    }
+
+    /// Rect of grid cell (`row`, `col`), in the same coordinates as `pos`.
+    /// Cells split the container evenly.
+    pub(crate) fn grid_cell(&self, row: usize, col: usize) -> Rect {
+        let cell = vec2(
+            self.size.x / self.props.columns.max(1) as f32,
+            self.size.y / self.props.rows.max(1) as f32,
+        );
+        Rect::from_min_size(
+            self.pos + vec2(col as f32 * cell.x, row as f32 * cell.y),
+            cell,
+        )
+    }
+
+    /// Area below a tab bar's header holding the active tab's children.
+    pub(crate) fn tab_content(&self) -> Rect {
+        Rect::from_min_max(
+            self.pos + vec2(0.0, TAB_HEADER_HEIGHT.min(self.size.y)),
+            self.pos + self.size,
+        )
+    }
+
+    /// Number of (row, col) slot rows: grid rows, or tabs.
+    pub(crate) fn slot_rows(&self) -> usize {
+        match self.kind {
+            WidgetKind::TabBar => self.props.items.len().max(1),
+            _ => self.props.rows.max(1),
+        }
+    }
+
+    /// Slot of this container that a child centered at `p` would go into.
+    /// Tab bars take children into the currently active tab.
+    pub(crate) fn slot_at(&self, p: Pos2) -> Option<(usize, usize)> {
+        match self.kind {
+            WidgetKind::Grid => self.grid_cell_at(p),
+            WidgetKind::TabBar if self.tab_content().contains(p) => {
+                Some((self.props.selected.min(self.slot_rows() - 1), 0))
+            }
+            _ => None,
+        }
+    }
+
+    /// Grid cell containing `p`, if it lies inside the container.
+    pub(crate) fn grid_cell_at(&self, p: Pos2) -> Option<(usize, usize)> {
+        let local = p - self.pos;
+        if local.x < 0.0 || local.y < 0.0 || local.x >= self.size.x || local.y >= self.size.y {
+            return None;
+        }
+        let col = (local.x / self.size.x * self.props.columns.max(1) as f32) as usize;
+        let row = (local.y / self.size.y * self.props.rows.max(1) as f32) as usize;
+        Some((row, col))
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -121,6 +298,7 @@ pub(crate) enum WidgetKind {
     TabBar,
     Columns,
     Window,
+    Grid,
 }
 
 impl WidgetKind {
@@ -163,6 +341,7 @@ impl WidgetKind {
             WidgetKind::Group
             | WidgetKind::ScrollBox
             | WidgetKind::Columns
+            | WidgetKind::Grid
             | WidgetKind::TabBar
             | WidgetKind::Window
             | WidgetKind::CollapsingHeader => WidgetCategory::Containers,
@@ -212,6 +391,7 @@ impl WidgetKind {
             WidgetKind::TabBar => "Tab Bar",
             WidgetKind::Columns => "Columns",
             WidgetKind::Window => "Window",
+            WidgetKind::Grid => "Grid",
         }
     }
 
@@ -257,6 +437,7 @@ impl WidgetKind {
             WidgetKind::Group,
             WidgetKind::ScrollBox,
             WidgetKind::Columns,
+            WidgetKind::Grid,
             WidgetKind::TabBar,
             WidgetKind::Window,
             WidgetKind::CollapsingHeader,
@@ -266,6 +447,30 @@ impl WidgetKind {
         ]
     }
 
+    /// Kinds whose generated code carries an accessible name and description
+    /// (see `WidgetProps::access_label`); the rest are layout or groups of
+    /// widgets
+    pub const fn is_accessible(&self) -> bool {
+        !matches!(
+            self,
+            WidgetKind::Separator
+                | WidgetKind::RadioGroup
+                | WidgetKind::Tree
+                | WidgetKind::Placeholder
+                | WidgetKind::Group
+                | WidgetKind::ScrollBox
+                | WidgetKind::Columns
+                | WidgetKind::Grid
+                | WidgetKind::TabBar
+                | WidgetKind::Window
+        )
+    }
+
+    /// Containers hold child widgets in slots (see `Widget::slot`)
+    pub const fn is_container(&self) -> bool {
+        matches!(self, WidgetKind::Grid | WidgetKind::TabBar)
+    }
+
     /// Returns the default size for a widget of this kind.
     /// Centralized to avoid duplication between spawn_widget and ghost preview.
     pub fn default_size(&self) -> egui::Vec2 {
@@ -302,9 +507,10 @@ impl WidgetKind {
             WidgetKind::Placeholder => vec2(200.0, 100.0),
             WidgetKind::Group => vec2(250.0, 150.0),
             WidgetKind::ScrollBox => vec2(200.0, 150.0),
-            WidgetKind::TabBar => vec2(300.0, 32.0),
+            WidgetKind::TabBar => vec2(360.0, 220.0),
             WidgetKind::Columns => vec2(300.0, 120.0),
             WidgetKind::Window => vec2(280.0, 180.0),
+            WidgetKind::Grid => vec2(320.0, 120.0),
         }
     }
 
@@ -496,11 +702,19 @@ impl WidgetKind {
                 text: "Window Title".into(),
                 ..Default::default()
             },
+            WidgetKind::Grid => WidgetProps {
+                columns: 2,
+                rows: 3,
+                striped: true,
+                min_col_width: 80.0,
+                ..Default::default()
+            },
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct WidgetProps {
     pub(crate) text: String,  // label/button/textedit placeholder
     pub(crate) checked: bool, // checkbox
@@ -525,8 +739,18 @@ pub(crate) struct WidgetProps {
     pub(crate) horizontal: bool,
     // enabled state
     pub(crate) enabled: bool,
-    // column count (for Columns widget)
+    // column count (for Columns and Grid widgets)
     pub(crate) columns: usize,
+    // project asset (for Image widget)
+    pub(crate) asset: Option<AssetId>,
+    // grid layout (for Grid widget)
+    pub(crate) rows: usize,
+    pub(crate) striped: bool,
+    pub(crate) spacing: [f32; 2],
+    pub(crate) min_col_width: f32,
+    // what screen readers announce, when the visible text isn't enough
+    pub(crate) access_label: String,
+    pub(crate) access_description: String,
 }
 
 impl Default for WidgetProps {
@@ -549,6 +773,13 @@ impl Default for WidgetProps {
             horizontal: false,
             enabled: true,
             columns: 2,
+            asset: None,
+            rows: 2,
+            striped: false,
+            spacing: [8.0, 4.0],
+            min_col_width: 40.0,
+            access_label: String::new(),
+            access_description: String::new(),
         }
     }
 }
@@ -557,10 +788,6 @@ pub(crate) fn snap_pos_with_grid(p: Pos2, grid: f32) -> Pos2 {
     pos2((p.x / grid).round() * grid, (p.y / grid).round() * grid)
 }
 
-pub(crate) fn escape(s: &str) -> String {
-    s.replace('\\', "\\\\").replace('"', "\\\"")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -580,19 +807,86 @@ mod tests {
         assert_eq!(snap_pos_with_grid(pos2(12.0, 20.0), 8.0), pos2(16.0, 24.0));
     }
 
+    fn widget_at(pos: Pos2, size: Vec2, anchor: Anchor) -> Widget {
+        Widget {
+            id: WidgetId::new(1),
+            kind: WidgetKind::Button,
+            pos,
+            size,
+            z: 1,
+            area: DockArea::Free,
+            props: WidgetKind::Button.default_props(),
+            anchor,
+            slot: None,
+        }
+    }
+
     #[test]
-    fn test_escape() {
-        // Test basic strings
-        assert_eq!(escape("hello"), "hello");
+    fn test_anchor_apply() {
+        let old = vec2(400.0, 300.0);
+        let new = vec2(500.0, 340.0);
+        let (pos, size) = (pos2(10.0, 20.0), vec2(100.0, 30.0));
+
+        let fixed = Anchor::default();
+        assert_eq!(fixed.apply(pos, size, old, new), (pos, size));
+
+        let br = Anchor {
+            h: HAnchor::Right,
+            v: VAnchor::Bottom,
+        };
+        assert_eq!(br.apply(pos, size, old, new), (pos2(110.0, 60.0), size));
+
+        let center = Anchor {
+            h: HAnchor::Center,
+            v: VAnchor::Center,
+        };
+        assert_eq!(center.apply(pos, size, old, new), (pos2(60.0, 40.0), size));
+
+        let stretch = Anchor {
+            h: HAnchor::Stretch,
+            v: VAnchor::Stretch,
+        };
+        assert_eq!(stretch.apply(pos, size, old, new), (pos, vec2(200.0, 70.0)));
+        // Stretched widgets never collapse below a usable size
+        let (_, shrunk) = stretch.apply(pos, size, old, vec2(100.0, 100.0));
+        assert_eq!(shrunk, MIN_ANCHORED_SIZE);
+    }
 
-        // Test backslash escaping
-        assert_eq!(escape("path\\to\\file"), "path\\\\to\\\\file");
+    #[test]
+    fn test_grid_cells() {
+        let mut grid = widget_at(pos2(100.0, 50.0), vec2(200.0, 90.0), Anchor::default());
+        grid.kind = WidgetKind::Grid;
+        grid.props = WidgetKind::Grid.default_props();
+        assert_eq!(
+            grid.grid_cell(1, 1),
+            Rect::from_min_size(pos2(200.0, 80.0), vec2(100.0, 30.0))
+        );
+        assert_eq!(grid.grid_cell_at(pos2(101.0, 51.0)), Some((0, 0)));
+        assert_eq!(grid.grid_cell_at(pos2(299.0, 139.0)), Some((2, 1)));
+        assert_eq!(grid.grid_cell_at(pos2(300.0, 60.0)), None);
+        assert_eq!(grid.grid_cell_at(pos2(99.0, 60.0)), None);
+    }
 
-        // Test quote escaping
-        assert_eq!(escape("say \"hello\""), "say \\\"hello\\\"");
+    #[test]
+    fn test_tab_bar_slots() {
+        let mut tabs = widget_at(pos2(0.0, 0.0), vec2(300.0, 200.0), Anchor::default());
+        tabs.kind = WidgetKind::TabBar;
+        tabs.props = WidgetKind::TabBar.default_props();
+        tabs.props.selected = 2;
+        assert_eq!(tabs.slot_rows(), 3);
+        // header strip is not a drop target
+        assert_eq!(tabs.slot_at(pos2(50.0, 10.0)), None);
+        assert_eq!(tabs.slot_at(pos2(50.0, 100.0)), Some((2, 0)));
+        assert_eq!(tabs.slot_at(pos2(350.0, 100.0)), None);
+    }
 
-        // Test combined
-        assert_eq!(escape("c:\\path\\\"file\""), "c:\\\\path\\\\\\\"file\\\"");
+    #[test]
+    fn test_widget_props_missing_fields() {
+        // Projects saved before a field existed still load, using defaults
+        let props: WidgetProps = serde_json::from_str(r#"{"text":"Old","columns":3}"#).unwrap();
+        assert_eq!(props.text, "Old");
+        assert_eq!(props.columns, 3);
+        assert_eq!(props.rows, WidgetProps::default().rows);
     }
 
     #[test]